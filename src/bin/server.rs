@@ -1,12 +1,16 @@
 use std::env;
-use dictionary_rs::{controller::{kafka::create_consumer, opensearch::{create, delete, IndexDocument}}, models::{browser::{browser_from_id, browsers, BrowserDocument}, form::{form_from_id, forms, FormDocument}, menu::allowed_menu, menu_item::MenuItemDocument, menu_tree::MenuTreeDocument, process::{process_from_id, processes, ProcessDocument}, role::RoleDocument, window::{window_from_id, windows, WindowDocument}}};
+use dictionary_rs::{error::DictionaryError, controller::{kafka::{create_consumer, create_dead_letter_producer, DeadLetter, DeadLetterKind, DeadLetterProducer, LoggingConsumer}, topic::{get_topic_registry, init_topic_registry, TopicError}, headers::{HeaderValidator, MessageHeaders, TokenValidator}, mapping::{check_mappings, register_index_templates, MappingDrift}, store::{create_dictionary_store, DictionaryStore, StorageType, StoreHealth}, opensearch::{reindex, BulkOperation, IndexDocument, OpenSearchConfig, OpenSearchPool}}, models::{dictionary_topic_registry, index_document_from_name, BatchGetRequest, browser::{browser_from_id, browsers, browsers_from_ids}, form::{form_from_id, forms, forms_from_ids}, menu::allowed_menu, process::{process_from_id, processes, processes_from_ids}, window::{window_from_id, windows, windows_from_ids}}};
 use dotenv::dotenv;
-use rdkafka::{Message, consumer::{CommitMode, Consumer}};
-use salvo::{conn::tcp::TcpAcceptor, cors::Cors, http::header, hyper::Method, prelude::*};
+use opensearch::OpenSearch;
+use rdkafka::{Message, message::BorrowedMessage, Offset, TopicPartitionList, consumer::{CommitMode, Consumer}};
+use salvo::{affix_state, conn::tcp::TcpAcceptor, cors::Cors, http::header, hyper::Method, prelude::*};
 extern crate serde_json;
 use serde::Serialize;
 use simple_logger::SimpleLogger;
 use futures::future::join_all;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::time::{sleep, timeout};
 
 #[tokio::main]
 async fn main() {
@@ -21,17 +25,74 @@ async fn main() {
 		}.to_owned()
 	};
 
+	// Storage of dictionary, used by HTTP handlers and Kafka consumer
+	let storage: StorageType = StorageType::from_env();
+	log::info!("Dictionary storage: {:?}", storage);
+	// OpenSearch client pool, created once and shared by the store and the admin API
+	let opensearch: Option<Arc<OpenSearchPool>> = if storage == StorageType::OpenSearch {
+		match OpenSearchPool::new(&OpenSearchConfig::from_env()) {
+			Ok(pool) => {
+				log::info!("OpenSearch client initialized");
+				Some(Arc::new(pool))
+			},
+			Err(error) => {
+				log::error!("OpenSearch client initialization error: {}", error);
+				return;
+			}
+		}
+	} else {
+		None
+	};
+	if let Some(pool) = &opensearch {
+		// Index templates, before any index is created by Kafka consumer
+		match register_index_templates(pool.client()).await {
+			Ok(templates) => log::info!("Index templates registered: {}", templates),
+			Err(error) => log::error!("Index templates registration error: {}", error)
+		};
+	}
+	let store: Arc<dyn DictionaryStore> = match create_dictionary_store(&storage, opensearch.clone()) {
+		Ok(value) => value,
+		Err(error) => {
+			log::error!("Dictionary storage initialization error: {}", error);
+			return;
+		}
+	};
+	// Handlers of Kafka topics, custom topics can be registered here
+	init_topic_registry(dictionary_topic_registry());
+
 	let host: String = "0.0.0.0:".to_owned() + &port;
 	log::info!("Server Address: {:?}", host.clone());
 	let acceptor: TcpAcceptor = TcpListener::new(host).bind().await;
 
+	let router: Router = routes(store.clone(), opensearch.clone());
 	let mut futures: Vec<tokio::task::JoinHandle<()>> = Vec::new();
 	futures.push(
 		tokio::spawn(
-			async move { Server::new(acceptor).serve(routes()).await; }
+			async move { Server::new(acceptor).serve(router).await; }
 		)
 	);
 
+	// Mapping drift of existing indices
+	if let Some(pool) = opensearch {
+		let mapping_auto_update: String = match env::var("MAPPING_AUTO_UPDATE") {
+			Ok(value) => value,
+			Err(_) => {
+				log::warn!("Variable `MAPPING_AUTO_UPDATE` Not found from enviroment, as default Y");
+				"Y".to_owned()
+			}.to_owned()
+		};
+		futures.push(
+			tokio::spawn(
+				async move {
+					match check_mappings(pool.client(), mapping_auto_update.trim().eq("Y")).await {
+						Ok(drifts) => log::info!("Mapping check finished, indices with differences: {}", drifts.len()),
+						Err(error) => log::error!("Mapping check error: {}", error)
+					}
+				}
+			)
+		);
+	}
+
 	// Kafka Queue
 	let kafka_enabled: String = match env::var("KAFKA_ENABLED") {
 		Ok(value) => value,
@@ -44,7 +105,7 @@ async fn main() {
 		log::info!("Kafka Consumer is enabled");
 		futures.push(
 			tokio::spawn(
-				async move { consume_queue().await; }
+				async move { consume_queue(store.as_ref()).await; }
 			)
 		);
 	} else {
@@ -54,7 +115,7 @@ async fn main() {
 	join_all(futures).await;
 }
 
-fn routes() -> Router {
+fn routes(_store: Arc<dyn DictionaryStore>, _opensearch: Option<Arc<OpenSearchPool>>) -> Router {
 	// TODO: Add support to allow requests from multiple origin
 	let allowed_origin: String = match env::var("ALLOWED_ORIGIN") {
 		Ok(value) => value,
@@ -66,12 +127,15 @@ fn routes() -> Router {
 
 	let allow_methods: Vec<Method> = vec![
 		Method::OPTIONS,
-		Method::GET
+		Method::GET,
+		// batch get routes
+		Method::POST
 	];
 	let allow_headers: Vec<header::HeaderName> = vec![
 		header::ACCESS_CONTROL_REQUEST_METHOD,
 		header::ACCESS_CONTROL_REQUEST_HEADERS,
-		header::AUTHORIZATION
+		header::AUTHORIZATION,
+		header::CONTENT_TYPE
 	];
 	// Send Device Info
 	let cors_handler = Cors::new()
@@ -83,6 +147,8 @@ fn routes() -> Router {
 
 	let router: Router = Router::new()
 		.hoop(cors_handler)
+		// dictionary store of handlers
+		.hoop(affix_state::inject(_store))
 		// /	root path
 		.options(options_response)
 		.get(get_system_info)
@@ -91,6 +157,12 @@ fn routes() -> Router {
 			Router::with_path("api")
 				.options(options_response)
 				.get(get_system_info)
+				.push(
+					// /api/health
+					Router::with_path("health")
+						.options(options_response)
+						.get(get_health)
+				)
 				.push(
 					// /api/security/menus
 					Router::with_path("security/menus")
@@ -106,6 +178,12 @@ fn routes() -> Router {
 								.options(options_response)
 								.get(get_system_info)
 						)
+						.push(
+							// /api/dictionary/browsers:batchGet
+							Router::with_path("browsers:batchGet")
+								.options(options_response)
+								.post(post_browsers_batch_get)
+						)
 						.push(
 							// /api/dictionary/browsers/
 							Router::with_path("browsers")
@@ -118,6 +196,12 @@ fn routes() -> Router {
 										.get(get_browsers)
 								)
 						)
+						.push(
+							// /api/dictionary/forms:batchGet
+							Router::with_path("forms:batchGet")
+								.options(options_response)
+								.post(post_forms_batch_get)
+						)
 						.push(
 							// /api/dictionary/forms/
 							Router::with_path("forms")
@@ -130,6 +214,12 @@ fn routes() -> Router {
 										.get(get_forms)
 								)
 						)
+						.push(
+							// /api/dictionary/processes:batchGet
+							Router::with_path("processes:batchGet")
+								.options(options_response)
+								.post(post_processes_batch_get)
+						)
 						.push(
 							// /api/dictionary/processes
 							Router::with_path("processes")
@@ -142,6 +232,12 @@ fn routes() -> Router {
 										.get(get_processes)
 								)
 						)
+						.push(
+							// /api/dictionary/windows:batchGet
+							Router::with_path("windows:batchGet")
+								.options(options_response)
+								.post(post_windows_batch_get)
+						)
 						.push(
 							// /api/dictionary/windows/
 							Router::with_path("windows")
@@ -158,6 +254,50 @@ fn routes() -> Router {
 		)
 	;
 
+	let admin_enabled: String = match env::var("ADMIN_ENABLED") {
+		Ok(value) => value,
+		Err(_) => {
+			log::warn!("Variable `ADMIN_ENABLED` Not found from enviroment, as default N");
+			"N".to_owned()
+		}.to_owned()
+	};
+	// admin operations are only supported by OpenSearch, and its requests
+	// require a token signed with the key of Kafka messages
+	let router: Router = match _opensearch {
+		Some(pool) if admin_enabled.trim().eq("Y") => match TokenValidator::from_env() {
+			Ok(Some(validator)) => {
+				log::info!("Admin API is enabled");
+				router.push(
+					// /api/admin
+					Router::with_path("api/admin")
+						// OpenSearch client and token validation of admin operations
+						.hoop(affix_state::inject(pool).inject(Arc::new(validator)))
+						.hoop(admin_auth)
+						.push(
+							// /api/admin/reindex
+							Router::with_path("reindex")
+								.post(post_reindex)
+						)
+						.push(
+							// /api/admin/mappings
+							Router::with_path("mappings")
+								.get(get_mappings_drift)
+								.post(post_mappings_update)
+						)
+				)
+			},
+			Ok(None) => {
+				log::error!("Admin API is disabled, `KAFKA_AUTH_SECRET` or `KAFKA_AUTH_PUBLIC_KEY_LOCATION` is required to validate its requests");
+				router
+			},
+			Err(error) => {
+				log::error!("Admin API is disabled, invalid token key: {}", error);
+				router
+			}
+		},
+		_ => router
+	};
+
 	log::info!("{:#?}", router);
 	router
 }
@@ -174,6 +314,23 @@ struct SystemInfoResponse {
 	kafka_queues: String,
 }
 
+//	Health of dictionary storage, it is answered with 503 when the storage is unavailable
+#[handler]
+async fn get_health<'a>(_req: &mut Request, _depot: &mut Depot, _res: &mut Response) {
+	let _store: &dyn DictionaryStore = match depot_store(_depot) {
+		Ok(store) => store,
+		Err(error) => {
+			render_error(_res, error);
+			return;
+		}
+	};
+	let health: StoreHealth = _store.health().await;
+	if !health.is_available {
+		_res.status_code(StatusCode::SERVICE_UNAVAILABLE);
+	}
+	_res.render(Json(health));
+}
+
 #[handler]
 async fn get_system_info<'a>(_req: &mut Request, _res: &mut Response) {
 	let version: String = match env::var("VERSION") {
@@ -217,11 +374,156 @@ async fn get_system_info<'a>(_req: &mut Request, _res: &mut Response) {
 #[derive(Serialize)]
 struct ErrorResponse {
 	status: u16,
+	code: String,
 	message: String
 }
 
+fn render_error(_res: &mut Response, _error: DictionaryError) {
+	let status: StatusCode = _error.status_code();
+	let error_response: ErrorResponse = ErrorResponse {
+		status: status.into(),
+		code: _error.code().to_owned(),
+		message: _error.to_string()
+	};
+	_res.render(
+		Json(error_response)
+	);
+	_res.status_code(status);
+}
+
+//	Dictionary store injected as state of the router
+fn depot_store(_depot: &Depot) -> Result<&dyn DictionaryStore, DictionaryError> {
+	match _depot.obtain::<Arc<dyn DictionaryStore>>() {
+		Ok(store) => Ok(store.as_ref()),
+		Err(_) => {
+			log::error!("Dictionary store not found on router state");
+			Err(DictionaryError::BackendUnavailable("Dictionary store is not available".to_owned()))
+		}
+	}
+}
+
+//	OpenSearch client injected as state of the admin router
+fn depot_opensearch(_depot: &Depot) -> Result<&OpenSearch, DictionaryError> {
+	match _depot.obtain::<Arc<OpenSearchPool>>() {
+		Ok(pool) => Ok(pool.client()),
+		Err(_) => {
+			log::error!("OpenSearch client not found on router state");
+			Err(DictionaryError::BackendUnavailable("OpenSearch client is not available".to_owned()))
+		}
+	}
+}
+
+//	Token of admin requests from `authorization` header (with or without
+//	`Bearer ` prefix), a request without valid token is not attended
+#[handler]
+async fn admin_auth<'a>(_req: &mut Request, _depot: &mut Depot, _res: &mut Response, _ctrl: &mut FlowCtrl) {
+	let _token: Option<String> = _req.headers().get(header::AUTHORIZATION)
+		.and_then(|value| value.to_str().ok())
+		.map(|value| value.trim())
+		.map(|value| value.strip_prefix("Bearer ").unwrap_or(value).trim().to_owned())
+		.filter(|value| !value.is_empty())
+	;
+	let result: Result<(), DictionaryError> = match (_depot.obtain::<Arc<TokenValidator>>(), _token) {
+		(Err(_), _) => {
+			log::error!("Token validator not found on router state");
+			Err(DictionaryError::Unauthorized("Token can not be validated".to_owned()))
+		},
+		(Ok(_), None) => Err(DictionaryError::Unauthorized("Header `authorization` is mandatory".to_owned())),
+		(Ok(validator), Some(token)) => validator.claims(&token)
+			.map(|_| ())
+			.map_err(DictionaryError::Unauthorized)
+	};
+	if let Err(error) = result {
+		log::warn!("Admin request {:?} rejected: {}", _req.uri().path(), error);
+		render_error(_res, error);
+		_ctrl.skip_rest();
+	}
+}
+
+#[derive(Serialize)]
+struct ReindexResponse {
+	index: String,
+	physical_index: String
+}
+
+#[handler]
+async fn post_reindex<'a>(_req: &mut Request, _depot: &mut Depot, _res: &mut Response) {
+	let _client: &OpenSearch = match depot_opensearch(_depot) {
+		Ok(client) => client,
+		Err(error) => {
+			render_error(_res, error);
+			return;
+		}
+	};
+	let _index: String = match _req.queries().get("index") {
+		Some(value) if !value.trim().is_empty() => value.trim().to_lowercase(),
+		_ => {
+			render_error(_res, DictionaryError::BadRequest("Index is Mandatory".to_owned()));
+			return;
+		}
+	};
+	let _document: Box<dyn IndexDocument> = match index_document_from_name(&_index) {
+		Some(document) => document,
+		None => {
+			render_error(_res, DictionaryError::BadRequest(format!("Index {:?} is not a dictionary index", _index)));
+			return;
+		}
+	};
+	match reindex(_client, _document.as_ref()).await {
+		Ok(physical_index) => {
+			_res.render(Json(ReindexResponse {
+				index: _index,
+				physical_index
+			}));
+		},
+		Err(error) => render_error(_res, error)
+	}
+}
+
+#[derive(Serialize)]
+struct MappingsResponse {
+	mappings: Vec<MappingDrift>
+}
+
+async fn render_mappings(_depot: &Depot, _res: &mut Response, _apply: bool) {
+	let _client: &OpenSearch = match depot_opensearch(_depot) {
+		Ok(client) => client,
+		Err(error) => {
+			render_error(_res, error);
+			return;
+		}
+	};
+	match check_mappings(_client, _apply).await {
+		Ok(mappings) => {
+			_res.render(Json(MappingsResponse {
+				mappings
+			}));
+		},
+		Err(error) => render_error(_res, error)
+	}
+}
+
+// Report the differences between stored and code mappings
 #[handler]
-async fn get_forms<'a>(_req: &mut Request, _res: &mut Response) {
+async fn get_mappings_drift<'a>(_req: &mut Request, _depot: &mut Depot, _res: &mut Response) {
+	render_mappings(_depot, _res, false).await;
+}
+
+// Add new fields to stored mappings, incompatible changes are only reported
+#[handler]
+async fn post_mappings_update<'a>(_req: &mut Request, _depot: &mut Depot, _res: &mut Response) {
+	render_mappings(_depot, _res, true).await;
+}
+
+#[handler]
+async fn get_forms<'a>(_req: &mut Request, _depot: &mut Depot, _res: &mut Response) {
+	let _store: &dyn DictionaryStore = match depot_store(_depot) {
+		Ok(store) => store,
+		Err(error) => {
+			render_error(_res, error);
+			return;
+		}
+	};
 	let mut _id: Option<String> = _req.param::<String>("id");
 	if _id.is_none() {
 		// fill with query url
@@ -232,63 +534,158 @@ async fn get_forms<'a>(_req: &mut Request, _res: &mut Response) {
 	let _language: Option<&String> = _req.queries().get("language");
 	let _dictionary_code: Option<&String> = _req.queries().get("dictionary_code");
 	let _search_value: Option<&String> = _req.queries().get("search_value");
+	let _search_mode: Option<&String> = _req.queries().get("search_mode");
+	let _include_matches: Option<&String> = _req.queries().get("include_matches");
+	let _page_size: Option<&String> = _req.queries().get("page_size");
+	let _page_token: Option<&String> = _req.queries().get("page_token");
 	if _id.is_some() {
-		match form_from_id(_id, _language, _dictionary_code).await {
+		match form_from_id(_store, _id, _language, _dictionary_code).await {
 			Ok(form) => _res.render(Json(form)),
-			Err(error) => {
-				let error_response: ErrorResponse = ErrorResponse {
-					status: StatusCode::INTERNAL_SERVER_ERROR.into(),
-					message: error.to_string()
-				};
-				_res.render(
-					Json(error_response)
-				);
-				_res.status_code(StatusCode::INTERNAL_SERVER_ERROR);
-			}
+			Err(error) => render_error(_res, error)
 		}
 	} else {
 		let _search_value: Option<&String> = _req.queries().get("search_value");
-		match forms(_language, _search_value, _dictionary_code).await {
+		match forms(_store, _language, _search_value, _search_mode, _include_matches, _page_size, _page_token, _dictionary_code).await {
 			Ok(forms_list) => {
 				_res.render(Json(forms_list));
 			},
-			Err(error) => {
-				let error_response: ErrorResponse = ErrorResponse {
-					status: StatusCode::INTERNAL_SERVER_ERROR.into(),
-					message: error.to_string()
-				};
-				_res.render(
-					Json(error_response)
-				);
-				_res.status_code(StatusCode::INTERNAL_SERVER_ERROR);
-			}
+			Err(error) => render_error(_res, error)
 		}
 	}
 }
 
 #[handler]
-async fn get_allowed_menu<'a>(_req: &mut Request, _res: &mut Response) {
+async fn get_allowed_menu<'a>(_req: &mut Request, _depot: &mut Depot, _res: &mut Response) {
+	let _store: &dyn DictionaryStore = match depot_store(_depot) {
+		Ok(store) => store,
+		Err(error) => {
+			render_error(_res, error);
+			return;
+		}
+	};
 	let _language: Option<&String> = _req.queries().get("language");
 	let _client_id: Option<&String> = _req.queries().get("client_id");
 	let _role_id: Option<&String> = _req.queries().get("role_id");
 	let _dictionary_code: Option<&String> = _req.queries().get("dictionary_code");
-	match allowed_menu(_language, _client_id, _role_id, _dictionary_code).await {
+	match allowed_menu(_store, _language, _client_id, _role_id, _dictionary_code).await {
         Ok(menu) => _res.render(Json(menu)),
-        Err(error) => {
-			let error_response: ErrorResponse = ErrorResponse {
-                status: StatusCode::INTERNAL_SERVER_ERROR.into(),
-                message: error.to_string()
-            };
-            _res.render(
-                Json(error_response)
-            );
-            _res.status_code(StatusCode::INTERNAL_SERVER_ERROR);
-        }
+        Err(error) => render_error(_res, error)
     }
 }
 
+//	Ids of a batch get body
+async fn batch_get_ids(_req: &mut Request) -> Result<Option<Vec<String>>, DictionaryError> {
+	match _req.parse_json::<BatchGetRequest>().await {
+		Ok(value) => Ok(value.ids),
+		Err(error) => Err(DictionaryError::BadRequest(format!("Invalid batch get body: {}", error)))
+	}
+}
+
+#[handler]
+async fn post_browsers_batch_get<'a>(_req: &mut Request, _depot: &mut Depot, _res: &mut Response) {
+	let _store: &dyn DictionaryStore = match depot_store(_depot) {
+		Ok(store) => store,
+		Err(error) => {
+			render_error(_res, error);
+			return;
+		}
+	};
+	let _ids: Option<Vec<String>> = match batch_get_ids(_req).await {
+		Ok(value) => value,
+		Err(error) => {
+			render_error(_res, error);
+			return;
+		}
+	};
+	let _language: Option<&String> = _req.queries().get("language");
+	let _dictionary_code: Option<&String> = _req.queries().get("dictionary_code");
+	match browsers_from_ids(_store, _ids, _language, _dictionary_code).await {
+		Ok(batch) => _res.render(Json(batch)),
+		Err(error) => render_error(_res, error)
+	}
+}
+
 #[handler]
-async fn get_processes<'a>(_req: &mut Request, _res: &mut Response) {
+async fn post_forms_batch_get<'a>(_req: &mut Request, _depot: &mut Depot, _res: &mut Response) {
+	let _store: &dyn DictionaryStore = match depot_store(_depot) {
+		Ok(store) => store,
+		Err(error) => {
+			render_error(_res, error);
+			return;
+		}
+	};
+	let _ids: Option<Vec<String>> = match batch_get_ids(_req).await {
+		Ok(value) => value,
+		Err(error) => {
+			render_error(_res, error);
+			return;
+		}
+	};
+	let _language: Option<&String> = _req.queries().get("language");
+	let _dictionary_code: Option<&String> = _req.queries().get("dictionary_code");
+	match forms_from_ids(_store, _ids, _language, _dictionary_code).await {
+		Ok(batch) => _res.render(Json(batch)),
+		Err(error) => render_error(_res, error)
+	}
+}
+
+#[handler]
+async fn post_processes_batch_get<'a>(_req: &mut Request, _depot: &mut Depot, _res: &mut Response) {
+	let _store: &dyn DictionaryStore = match depot_store(_depot) {
+		Ok(store) => store,
+		Err(error) => {
+			render_error(_res, error);
+			return;
+		}
+	};
+	let _ids: Option<Vec<String>> = match batch_get_ids(_req).await {
+		Ok(value) => value,
+		Err(error) => {
+			render_error(_res, error);
+			return;
+		}
+	};
+	let _language: Option<&String> = _req.queries().get("language");
+	let _dictionary_code: Option<&String> = _req.queries().get("dictionary_code");
+	match processes_from_ids(_store, _ids, _language, _dictionary_code).await {
+		Ok(batch) => _res.render(Json(batch)),
+		Err(error) => render_error(_res, error)
+	}
+}
+
+#[handler]
+async fn post_windows_batch_get<'a>(_req: &mut Request, _depot: &mut Depot, _res: &mut Response) {
+	let _store: &dyn DictionaryStore = match depot_store(_depot) {
+		Ok(store) => store,
+		Err(error) => {
+			render_error(_res, error);
+			return;
+		}
+	};
+	let _ids: Option<Vec<String>> = match batch_get_ids(_req).await {
+		Ok(value) => value,
+		Err(error) => {
+			render_error(_res, error);
+			return;
+		}
+	};
+	let _language: Option<&String> = _req.queries().get("language");
+	let _dictionary_code: Option<&String> = _req.queries().get("dictionary_code");
+	match windows_from_ids(_store, _ids, _language, _dictionary_code).await {
+		Ok(batch) => _res.render(Json(batch)),
+		Err(error) => render_error(_res, error)
+	}
+}
+
+#[handler]
+async fn get_processes<'a>(_req: &mut Request, _depot: &mut Depot, _res: &mut Response) {
+	let _store: &dyn DictionaryStore = match depot_store(_depot) {
+		Ok(store) => store,
+		Err(error) => {
+			render_error(_res, error);
+			return;
+		}
+	};
 	let mut _id: Option<String> = _req.param::<String>("id");
 	if _id.is_none() {
 		// fill with query url
@@ -299,41 +696,34 @@ async fn get_processes<'a>(_req: &mut Request, _res: &mut Response) {
 	let _language: Option<&String> = _req.queries().get("language");
 	let _dictionary_code: Option<&String> = _req.queries().get("dictionary_code");
 	let _search_value: Option<&String> = _req.queries().get("search_value");
+	let _search_mode: Option<&String> = _req.queries().get("search_mode");
+	let _include_matches: Option<&String> = _req.queries().get("include_matches");
+	let _page_size: Option<&String> = _req.queries().get("page_size");
+	let _page_token: Option<&String> = _req.queries().get("page_token");
 	if _id.is_some() {
-		match process_from_id(_id, _language, _dictionary_code).await {
+		match process_from_id(_store, _id, _language, _dictionary_code).await {
             Ok(process) => _res.render(Json(process)),
-			Err(error) => {
-				let error_response: ErrorResponse = ErrorResponse {
-					status: StatusCode::INTERNAL_SERVER_ERROR.into(),
-					message: error.to_string()
-				};
-				_res.render(
-					Json(error_response)
-				);
-				_res.status_code(StatusCode::INTERNAL_SERVER_ERROR);
-			}
+			Err(error) => render_error(_res, error)
         }
     } else {
-        match processes(_language, _search_value, _dictionary_code).await {
+        match processes(_store, _language, _search_value, _search_mode, _include_matches, _page_size, _page_token, _dictionary_code).await {
             Ok(processes_list) => {
                 _res.render(Json(processes_list));
             },
-			Err(error) => {
-				let error_response = ErrorResponse {
-					status: StatusCode::INTERNAL_SERVER_ERROR.into(),
-					message: error.to_string()
-				};
-				_res.render(
-					Json(error_response)
-				);
-				_res.status_code(StatusCode::INTERNAL_SERVER_ERROR);
-			}
+			Err(error) => render_error(_res, error)
         }
     }
 }
 
 #[handler]
-async fn get_browsers<'a>(_req: &mut Request, _res: &mut Response) {
+async fn get_browsers<'a>(_req: &mut Request, _depot: &mut Depot, _res: &mut Response) {
+	let _store: &dyn DictionaryStore = match depot_store(_depot) {
+		Ok(store) => store,
+		Err(error) => {
+			render_error(_res, error);
+			return;
+		}
+	};
 	let mut _id: Option<String> = _req.param::<String>("id");
 	if _id.is_none() {
 		// fill with query url
@@ -344,41 +734,34 @@ async fn get_browsers<'a>(_req: &mut Request, _res: &mut Response) {
 	let _language: Option<&String> = _req.queries().get("language");
 	let _dictionary_code: Option<&String> = _req.queries().get("dictionary_code");
 	let _search_value: Option<&String> = _req.queries().get("search_value");
+	let _search_mode: Option<&String> = _req.queries().get("search_mode");
+	let _include_matches: Option<&String> = _req.queries().get("include_matches");
+	let _page_size: Option<&String> = _req.queries().get("page_size");
+	let _page_token: Option<&String> = _req.queries().get("page_token");
 	if _id.is_some() {
-		match browser_from_id(_id, _language, _dictionary_code).await {
+		match browser_from_id(_store, _id, _language, _dictionary_code).await {
             Ok(browser) => _res.render(Json(browser)),
-			Err(error) => {
-				let error_response: ErrorResponse = ErrorResponse {
-					status: StatusCode::INTERNAL_SERVER_ERROR.into(),
-					message: error.to_string()
-				};
-				_res.render(
-					Json(error_response)
-				);
-				_res.status_code(StatusCode::INTERNAL_SERVER_ERROR);
-			}
+			Err(error) => render_error(_res, error)
         }
     } else {
-        match browsers(_language, _search_value, _dictionary_code).await {
+        match browsers(_store, _language, _search_value, _search_mode, _include_matches, _page_size, _page_token, _dictionary_code).await {
             Ok(browsers_list) => {
                 _res.render(Json(browsers_list));
             },
-			Err(error) => {
-				let error_response: ErrorResponse = ErrorResponse {
-					status: StatusCode::INTERNAL_SERVER_ERROR.into(),
-					message: error.to_string()
-				};
-				_res.render(
-					Json(error_response)
-				);
-				_res.status_code(StatusCode::INTERNAL_SERVER_ERROR);
-			}
+			Err(error) => render_error(_res, error)
         }
     }
 }
 
 #[handler]
-async fn get_windows<'a>(_req: &mut Request, _res: &mut Response) {
+async fn get_windows<'a>(_req: &mut Request, _depot: &mut Depot, _res: &mut Response) {
+	let _store: &dyn DictionaryStore = match depot_store(_depot) {
+		Ok(store) => store,
+		Err(error) => {
+			render_error(_res, error);
+			return;
+		}
+	};
 	let mut _id: Option<String> = _req.param::<String>("id");
 	if _id.is_none() {
 		_id = _req.queries().get("id").map(|s| s.to_owned());
@@ -388,40 +771,42 @@ async fn get_windows<'a>(_req: &mut Request, _res: &mut Response) {
 	let _language: Option<&String> = _req.queries().get("language");
 	let _dictionary_code: Option<&String> = _req.queries().get("dictionary_code");
 	let _search_value: Option<&String> = _req.queries().get("search_value");
+	let _search_mode: Option<&String> = _req.queries().get("search_mode");
+	let _include_matches: Option<&String> = _req.queries().get("include_matches");
+	let _page_size: Option<&String> = _req.queries().get("page_size");
+	let _page_token: Option<&String> = _req.queries().get("page_token");
 	if _id.is_some() {
-		match window_from_id(_id, _language, _dictionary_code).await {
+		match window_from_id(_store, _id, _language, _dictionary_code).await {
             Ok(window) => _res.render(Json(window)),
-			Err(error) => {
-				let error_response: ErrorResponse = ErrorResponse {
-					status: StatusCode::INTERNAL_SERVER_ERROR.into(),
-					message: error.to_string()
-				};
-				_res.render(
-					Json(error_response)
-				);
-				_res.status_code(StatusCode::INTERNAL_SERVER_ERROR);
-			}
+			Err(error) => render_error(_res, error)
         }
     } else {
-        match windows(_language, _search_value, _dictionary_code).await {
+        match windows(_store, _language, _search_value, _search_mode, _include_matches, _page_size, _page_token, _dictionary_code).await {
             Ok(windows_list) => {
                 _res.render(Json(windows_list));
             },
-			Err(error) => {
-				let error_response: ErrorResponse = ErrorResponse {
-					status: StatusCode::INTERNAL_SERVER_ERROR.into(),
-					message: error.to_string()
-				};
-				_res.render(
-					Json(error_response)
-				);
-				_res.status_code(StatusCode::INTERNAL_SERVER_ERROR);
-            }
+			Err(error) => render_error(_res, error)
         }
     }
 }
 
-async fn consume_queue() {
+fn get_env_number(_key: &str, _default_value: u64) -> u64 {
+	match env::var(_key) {
+		Ok(value) => match value.trim().parse::<u64>() {
+			Ok(number) => number,
+			Err(error) => {
+				log::warn!("Variable `{}` with invalid value {:?} ({}), as default {}", _key, value, error, _default_value);
+				_default_value
+			}
+		},
+		Err(_) => {
+			log::warn!("Variable `{}` Not found from enviroment, as default {}", _key, _default_value);
+			_default_value
+		}
+	}
+}
+
+async fn consume_queue(_store: &dyn DictionaryStore) {
 	let kafka_host: String = match env::var("KAFKA_HOST") {
 		Ok(value) => value,
 		Err(_) => {
@@ -446,198 +831,326 @@ async fn consume_queue() {
 		}.to_owned()
 	};
 
+	// Batch of messages written with a single bulk request
+	let batch_size: usize = get_env_number("KAFKA_BATCH_SIZE", 500).max(1) as usize;
+	let batch_timeout: Duration = Duration::from_millis(get_env_number("KAFKA_BATCH_TIMEOUT", 1000));
+	let batch_retries: u64 = get_env_number("KAFKA_BATCH_RETRIES", 3);
+
 	let topics_list: Vec<&str> = kafka_queues.split_whitespace().collect();
 	log::info!("Kafka Topics to Subscribe: {:?}", topics_list.to_owned());
+	for topic in topics_list.iter() {
+		if get_topic_registry().handler(topic).is_none() {
+			log::warn!("Topic {:?} without handler, its messages can not be processed", topic);
+		}
+	}
 
+	// a token key that can not be loaded would reject all messages
+	let header_validator: HeaderValidator = match HeaderValidator::from_env() {
+		Ok(value) => value,
+		Err(error) => {
+			log::error!("Kafka consumer not started, invalid token key: {}", error);
+			return;
+		}
+	};
+	let dead_letter: Option<DeadLetterProducer> = match create_dead_letter_producer(&kafka_host) {
+		Ok(value) => value,
+		Err(error) => {
+			log::error!("Kafka consumer not started, dead letter producer error: {}", error);
+			return;
+		}
+	};
 	let consumer_result= create_consumer(&kafka_host, &kafka_group, &topics_list);
-    match consumer_result {
-        Ok(consumer) => {
-            loop {
-                match consumer.recv().await {
-                    Err(e) => log::error!("Kafka error: {}", e),
-                    Ok(message) => {
-						let key: &str = match message.key_view::<str>() {
-                            None => "",
-                            Some(Ok(s)) => s,
-                            Some(Err(e)) => {
-								log::error!("Error while deserializing message key: {:?}", e);
-                                ""
-                            }
-                        };
-						let event_type: String = key.replace("\"", "");
+	match consumer_result {
+		Ok(consumer) => {
+			let mut batch: Vec<BatchMessage> = Vec::new();
+			let mut batch_started: Instant = Instant::now();
+			loop {
+				// wait the remaining time of current batch, or any time without batch
+				let _message = if batch.is_empty() {
+					Ok(consumer.recv().await)
+				} else {
+					let elapsed: Duration = batch_started.elapsed();
+					timeout(batch_timeout.saturating_sub(elapsed), consumer.recv()).await
+				};
+				match _message {
+					Err(_) => {
+						// batch timeout
+						if flush_batch(_store, &consumer, &mut batch, batch_retries, dead_letter.as_ref()).await {
+							wait_for_store(_store, &consumer).await;
+						}
+						continue;
+					},
+					Ok(Err(e)) => log::error!("Kafka error: {}", e),
+					Ok(Ok(message)) => {
 						let topic: &str = message.topic();
 						if (topics_list.contains(&topic)) == false {
 							log::warn!("Topic {:?} not allowed to be processed", topic);
 							continue;
 						}
-
-						let payload: &str = match message.payload_view::<str>() {
-							None => "",
-							Some(Ok(s)) => s,
-							Some(Err(e)) => {
-								log::error!("Error while deserializing message payload: {:?}", e);
-								""
+						// messages with invalid headers are rejected
+						let _headers: Result<MessageHeaders, TopicError> = MessageHeaders::from_message(&message);
+						let trace_id: Option<String> = _headers.as_ref().ok().and_then(|headers| headers.trace_id.to_owned());
+						let _operation: Result<BulkOperation, TopicError> = _headers
+							.and_then(|headers| header_validator.validate(&headers))
+							.and_then(|_| message_operation(&message))
+							.map(|operation| BulkOperation {
+								trace_id: trace_id.to_owned(),
+								..operation
+							})
+						;
+						// a message that can not be processed is done once it is sent to dead letter topic
+						let is_processed: bool = match &_operation {
+							Ok(operation) => {
+								log::debug!("Event `{:?}` into index {:} with id {:} (trace {})", operation.action, operation.document.index_name(), operation.document.id(), operation.trace());
+								false
+							},
+							Err(error) => {
+								log::warn!("Topic: {:?}, {} (trace {})", topic, error.message, trace_id.as_deref().unwrap_or("-"));
+								send_dead_letter(dead_letter.as_ref(), &message, error, trace_id.as_deref()).await
 							}
 						};
-                        if topic == "menu_item" {
-							let _document: MenuItemDocument = match serde_json::from_str(payload) {
-                                Ok(value) => value,
-                                Err(error) => {
-                                    log::warn!("Topic: {:?}, {}", topic, error);
-                                    MenuItemDocument {
-                                        document: None
-                                    }
-                                },
-                            };
-                            if _document.document.is_some() {
-                                let _menu_document: &dyn IndexDocument = &(_document.document.unwrap());
-                                match process_index(event_type, _menu_document).await {
-                                    Ok(_) => consumer.commit_message(&message, CommitMode::Async).unwrap(),
-                                    Err(error) => log::warn!("Document: {:?} {}", _menu_document.index_name(), error)
-                                }
-                            }
-                        } else if topic == "menu_tree" {
-							let _document: MenuTreeDocument = match serde_json::from_str(payload) {
-                                Ok(value) => value,
-                                Err(error) => {
-                                    log::warn!("Topic: {:?}, {}", topic, error);
-                                    MenuTreeDocument {
-                                        document: None
-                                    }
-                                },
-                            };
-                            if _document.document.is_some() {
-                                let _menu_document: &dyn IndexDocument = &(_document.document.unwrap());
-                                match process_index(event_type, _menu_document).await {
-                                    Ok(_) => consumer.commit_message(&message, CommitMode::Async).unwrap(),
-                                    Err(error) => log::warn!("Document: {:?} {}", _menu_document.index_name(), error)
-                                }
-                            }
-                        } else if topic == "role" {
-							let _document: RoleDocument = match serde_json::from_str(payload) {
-                                Ok(value) => value,
-                                Err(error) => {
-                                    log::warn!("Topic: {:?}, {}", topic, error);
-                                    RoleDocument {
-                                        document: None
-                                    }
-                                },
-                            };
-                            if _document.document.is_some() {
-                                let _menu_document: &dyn IndexDocument = &(_document.document.unwrap());
-                                match process_index(event_type, _menu_document).await {
-                                    Ok(_) => consumer.commit_message(&message, CommitMode::Async).unwrap(),
-                                    Err(error) => log::warn!("Document: {:?} {}", _menu_document.index_name(), error)
-                                }
-                            }
-                        } else if topic == "process" {
-							let _document: ProcessDocument = match serde_json::from_str(payload) {
-                                Ok(value) => value,
-                                Err(error) => {
-                                    log::warn!("Topic: {:?}, {}", topic, error);
-                                    ProcessDocument {
-                                        document: None
-                                    }
-                                },
-                            };
-                            if _document.document.is_some() {
-                                let _process_document: &dyn IndexDocument = &(_document.document.unwrap());
-                                match process_index(event_type, _process_document).await {
-                                    Ok(_) => consumer.commit_message(&message, CommitMode::Async).unwrap(),
-                                    Err(error) => log::warn!("Document: {:?} {}", _process_document.index_name(), error)
-                                }
-                            }
-                        } else if topic == "browser" {
-							let _document: BrowserDocument = match serde_json::from_str(payload) {
-                                Ok(value) => value,
-                                Err(error) => {
-                                    log::warn!("Topic: {:?}, {}", topic, error);
-                                    BrowserDocument {
-                                        document: None
-                                    }
-                                },
-                            };
-                            if _document.document.is_some() {
-                                let _browser_document: &dyn IndexDocument = &(_document.document.unwrap());
-                                match process_index(event_type, _browser_document).await {
-                                    Ok(_) => consumer.commit_message(&message, CommitMode::Async).unwrap(),
-                                    Err(error) => log::warn!("Document: {:?} {}", _browser_document.index_name(), error)
-                                }
-                            }
-                        } else if topic == "window" {
-							let _document: WindowDocument = match serde_json::from_str(payload) {
-                                Ok(value) => value,
-                                Err(error) => {
-                                    log::warn!("Topic: {:?}, {}", topic, error);
-                                    WindowDocument {
-                                        document: None
-                                    }
-                                },
-                            };
-                            if _document.document.is_some() {
-                                let _window_document: &dyn IndexDocument = &(_document.document.unwrap());
-                                match process_index(event_type, _window_document).await {
-                                    Ok(_) => consumer.commit_message(&message, CommitMode::Async).unwrap(),
-                                    Err(error) => log::warn!("Document: {:?} {}", _window_document.index_name(), error)
-                                }
-                            }
-						} else if topic == "form" {
-							let _document: FormDocument = match serde_json::from_str(payload) {
-								Ok(value) => value,
-								Err(error) => {
-									log::warn!("Topic: {:?}, {}", topic, error);
-									FormDocument {
-										document: None
-									}
-								},
-							};
-							if _document.document.is_some() {
-								let _form_document: &dyn IndexDocument = &(_document.document.unwrap());
-								match process_index(event_type, _form_document).await {
-									Ok(_) => consumer.commit_message(&message, CommitMode::Async).unwrap(),
-									Err(error) => log::warn!("Document: {:?} {}", _form_document.index_name(), error)
-								}
+						if batch.is_empty() {
+							batch_started = Instant::now();
+						}
+						batch.push(
+							BatchMessage {
+								topic: topic.to_owned(),
+								partition: message.partition(),
+								offset: message.offset(),
+								// kept for dead letter topic
+								key: message.key().map(|value| value.to_vec()),
+								payload: message.payload().map(|value| value.to_vec()),
+								operation: _operation.ok(),
+								is_processed,
+								trace_id,
 							}
-                        }
-                        // TODO: Add token header
-                        // if let Some(headers) = message.headers() {
-                        //     for header in headers.iter() {
-                        //         log::info!("  Header {:#?}: {:?}", header.key, header.value);
-                        //     }
-                        // }
-                    }
-                };
-            }
-        },
-        Err(error) => log::error!("Consume Queue Error {}", error),
-    };
-}
-
-async fn process_index(_event_type: String, _document: &dyn IndexDocument) -> Result<bool, std::string::String> {
-	let index_name: String = _document.index_name();
-	let id: String = _document.id();
-	log::debug!("Event `{:}` into index {:} with id {:} ", _event_type, index_name, id);
-
-    if _event_type.eq("new") {
-        match create(_document).await {
-            Ok(_) => return Ok(true),
-            Err(error) => return Err(error.to_string())
-        };  
-    } else if _event_type.eq("update") {
-        match delete(_document).await {
-            Ok(_) => {
-                match create(_document).await {
-                    Ok(_) => return Ok(true),
-                    Err(error) => return Err(error.to_string())
-                }
-            },
-            Err(error) => return Err(error.to_string())
-        };
-    } else if _event_type.eq("delete") {
-        match delete(_document).await {
-            Ok(_) => return Ok(true),
-            Err(error) => return Err(error.to_string())
-        };
-    }
-    Ok(true)
+						);
+					}
+				};
+				if batch.len() >= batch_size && flush_batch(_store, &consumer, &mut batch, batch_retries, dead_letter.as_ref()).await {
+					wait_for_store(_store, &consumer).await;
+				}
+			}
+		},
+		Err(error) => log::error!("Consume Queue Error {}", error),
+	};
+}
+
+// Operation of a message from the handler of its topic
+fn message_operation(_message: &BorrowedMessage<'_>) -> Result<BulkOperation, TopicError> {
+	let key: &str = match _message.key_view::<str>() {
+		None => "",
+		Some(Ok(s)) => s,
+		Some(Err(e)) => {
+			log::error!("Error while deserializing message key: {:?}", e);
+			return Err(TopicError::deserialization(format!("Invalid message key: {}", e)));
+		}
+	};
+	let event_type: String = key.replace("\"", "");
+	let payload: &str = match _message.payload_view::<str>() {
+		None => "",
+		Some(Ok(s)) => s,
+		Some(Err(e)) => {
+			log::error!("Error while deserializing message payload: {:?}", e);
+			return Err(TopicError::deserialization(format!("Invalid message payload: {}", e)));
+		}
+	};
+	get_topic_registry().operation(_message.topic(), &event_type, payload)
+}
+
+// Message that can not be processed, it is only skipped without dead letter
+// topic. Returns `false` when it must be delivered again
+async fn send_dead_letter(_dead_letter: Option<&DeadLetterProducer>, _message: &BorrowedMessage<'_>, _error: &TopicError, _trace_id: Option<&str>) -> bool {
+	let letter: DeadLetter = DeadLetter {
+		topic: _message.topic(),
+		partition: _message.partition(),
+		offset: _message.offset(),
+		key: _message.key(),
+		payload: _message.payload(),
+		kind: _error.kind,
+		message: _error.message.to_owned(),
+		trace_id: _trace_id,
+	};
+	match _dead_letter {
+		Some(producer) => producer.send(&letter).await.is_ok(),
+		None => {
+			log::warn!("Message skipped (topic {:?}, partition {}, offset {}, trace {}): {} {}", letter.topic, letter.partition, letter.offset, _trace_id.unwrap_or("-"), letter.kind.code(), letter.message);
+			true
+		}
+	}
+}
+
+struct BatchMessage {
+	topic: String,
+	partition: i32,
+	offset: i64,
+	key: Option<Vec<u8>>,
+	payload: Option<Vec<u8>>,
+	//	Without operation when the message can not be processed
+	operation: Option<BulkOperation>,
+	//	Written or sent to dead letter topic, its offset can be committed
+	is_processed: bool,
+	//	Trace id from headers, for logs
+	trace_id: Option<String>,
+}
+
+// Write the batch with bulk requests. The documents that fail because the
+// storage is unavailable are retried, and after the retries they are kept to
+// be delivered again, the documents rejected by the storage are sent to dead
+// letter topic (or skipped without it). Offsets are committed up to the first message not processed
+// of each partition, and the partition is rewound to it. Returns `true` when
+// the storage is unavailable
+async fn flush_batch(_store: &dyn DictionaryStore, _consumer: &LoggingConsumer, _batch: &mut Vec<BatchMessage>, _retries: u64, _dead_letter: Option<&DeadLetterProducer>) -> bool {
+	if _batch.is_empty() {
+		return false;
+	}
+	log::debug!("Writing batch with {} messages", _batch.len());
+
+	let mut messages: Vec<BatchMessage> = std::mem::take(_batch);
+	let mut pending: Vec<usize> = messages.iter()
+		.enumerate()
+		.filter(|(_, message)| !message.is_processed && message.operation.is_some())
+		.map(|(index, _)| index)
+		.collect()
+	;
+	let mut is_unavailable: bool = false;
+	let mut attempt: u64 = 0;
+	while !pending.is_empty() {
+		let operations: Vec<BulkOperation> = pending.iter()
+			.filter_map(|index| messages[*index].operation.clone())
+			.collect()
+		;
+		// a failed request is not a rejection of its documents
+		let (results, is_request_error): (Vec<Result<bool, DictionaryError>>, bool) = match _store.bulk(&operations).await {
+			Ok(values) => (values, false),
+			Err(error) => (operations.iter().map(|_| Err(error.to_owned())).collect(), true)
+		};
+		let mut failed: Vec<usize> = Vec::new();
+		for (index, result) in pending.into_iter().zip(results) {
+			let message: &mut BatchMessage = &mut messages[index];
+			let error: DictionaryError = match result {
+				// a stale document is not written, but it is processed
+				Ok(_) => {
+					message.is_processed = true;
+					continue;
+				},
+				Err(error) => error
+			};
+			if is_request_error || matches!(error, DictionaryError::BackendUnavailable(_)) {
+				if attempt < _retries {
+					failed.push(index);
+				} else {
+					log::warn!("Document: {:?} {}, it will be delivered again (topic {:?}, partition {}, offset {}, trace {})", message.operation.as_ref().map(|operation| operation.document.index_name()), error, message.topic, message.partition, message.offset, message.trace_id.as_deref().unwrap_or("-"));
+					is_unavailable = true;
+				}
+				continue;
+			}
+			log::warn!("Document: {:?} {} (topic {:?}, partition {}, offset {}, trace {})", message.operation.as_ref().map(|operation| operation.document.index_name()), error, message.topic, message.partition, message.offset, message.trace_id.as_deref().unwrap_or("-"));
+			// without dead letter topic the rejected message is only skipped
+			message.is_processed = match _dead_letter {
+				Some(producer) => {
+					let letter: DeadLetter = DeadLetter {
+						topic: &message.topic,
+						partition: message.partition,
+						offset: message.offset,
+						key: message.key.as_deref(),
+						payload: message.payload.as_deref(),
+						kind: DeadLetterKind::Indexing,
+						message: error.to_string(),
+						trace_id: message.trace_id.as_deref(),
+					};
+					producer.send(&letter).await.is_ok()
+				},
+				None => {
+					log::warn!("Message skipped (topic {:?}, partition {}, offset {}, trace {}): {} {}", message.topic, message.partition, message.offset, message.trace_id.as_deref().unwrap_or("-"), DeadLetterKind::Indexing.code(), error);
+					true
+				}
+			};
+		}
+		if failed.is_empty() {
+			break;
+		}
+		attempt += 1;
+		log::warn!("Retrying {} failed messages of batch, attempt {}", failed.len(), attempt);
+		sleep(Duration::from_millis(500 * 2_u64.pow(attempt.min(10) as u32))).await;
+		pending = failed;
+	}
+
+	commit_batch(_consumer, &messages);
+	is_unavailable
+}
+
+// Pause the consumption until the storage is available again, the partitions
+// with messages not written were rewound to them
+async fn wait_for_store(_store: &dyn DictionaryStore, _consumer: &LoggingConsumer) {
+	let assignment: TopicPartitionList = match _consumer.assignment() {
+		Ok(value) => value,
+		Err(error) => {
+			log::error!("Error getting Kafka assignment: {}", error);
+			TopicPartitionList::new()
+		}
+	};
+	if let Err(error) = _consumer.pause(&assignment) {
+		log::error!("Error pausing Kafka consumption: {}", error);
+	}
+	let mut attempt: u32 = 0;
+	loop {
+		sleep(Duration::from_millis(1000 * 2_u64.pow(attempt.min(6)))).await;
+		let health: StoreHealth = _store.health().await;
+		if health.is_available {
+			log::info!("Storage {} is available, Kafka consumption is resumed", health.storage);
+			break;
+		}
+		log::warn!("Storage {} is unavailable, Kafka consumption is paused: {}", health.storage, health.message.unwrap_or_default());
+		attempt += 1;
+	}
+	if let Err(error) = _consumer.resume(&assignment) {
+		log::error!("Error resuming Kafka consumption: {}", error);
+	}
+}
+
+// Commit the offsets of processed messages, a partition with a message not
+// processed is committed and rewound to it, so it is delivered again
+fn commit_batch(_consumer: &LoggingConsumer, _messages: &[BatchMessage]) {
+	// next offset of each partition, and if it must be rewound
+	let mut partitions: Vec<(&str, i32, i64, bool)> = Vec::new();
+	for message in _messages.iter() {
+		let position: Option<usize> = partitions.iter()
+			.position(|(topic, partition, _, _)| message.topic.eq(topic) && message.partition == *partition)
+		;
+		let index: usize = match position {
+			Some(value) => value,
+			None => {
+				partitions.push((&message.topic, message.partition, message.offset, false));
+				partitions.len() - 1
+			}
+		};
+		let (_, _, offset, is_rewind) = &mut partitions[index];
+		if *is_rewind {
+			continue;
+		}
+		if message.is_processed {
+			*offset = message.offset + 1;
+		} else {
+			*offset = message.offset;
+			*is_rewind = true;
+		}
+	}
+
+	let mut offsets: TopicPartitionList = TopicPartitionList::new();
+	for (topic, partition, offset, is_rewind) in partitions.iter() {
+		if let Err(error) = offsets.add_partition_offset(topic, *partition, Offset::Offset(*offset)) {
+			log::error!("Error adding offset {:?} {} {}: {}", topic, partition, offset, error);
+		}
+		if *is_rewind {
+			log::error!("Message not processed (topic {:?}, partition {}, offset {}), it will be delivered again", topic, partition, offset);
+			if let Err(error) = _consumer.seek(topic, *partition, Offset::Offset(*offset), Duration::from_secs(10)) {
+				log::error!("Error rewinding {:?} {} to offset {}: {}", topic, partition, offset, error);
+			}
+		}
+	}
+	match _consumer.commit(&offsets, CommitMode::Async) {
+		Ok(_) => {},
+		Err(error) => log::error!("Error committing offsets {:?}: {}", offsets, error)
+	};
 }
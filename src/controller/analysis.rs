@@ -0,0 +1,81 @@
+use serde_json::{json, Value};
+
+use crate::models::DICTIONARY_INDICES;
+
+//	Languages with stemming analyzer, as `dictionary_es`
+pub const ANALYZER_LANGUAGES: [&str; 3] = ["en", "es", "pt"];
+
+//	Analyzer of indices without language or with an unsupported language
+pub const STANDARD_ANALYZER: &str = "dictionary_standard";
+
+//	Analyzers of dictionary text fields, all of them ignore case and accents
+pub fn analysis_settings() -> Value {
+	json!({
+		"analysis": {
+			"filter": {
+				"dictionary_en_possessive": {
+					"type": "stemmer",
+					"language": "possessive_english"
+				},
+				"dictionary_en_stemmer": {
+					"type": "stemmer",
+					"language": "light_english"
+				},
+				"dictionary_es_stemmer": {
+					"type": "stemmer",
+					"language": "light_spanish"
+				},
+				"dictionary_pt_stemmer": {
+					"type": "stemmer",
+					"language": "light_portuguese"
+				}
+			},
+			"analyzer": {
+				"dictionary_standard": {
+					"type": "custom",
+					"tokenizer": "standard",
+					"filter": ["lowercase", "asciifolding"]
+				},
+				"dictionary_en": {
+					"type": "custom",
+					"tokenizer": "standard",
+					"filter": ["dictionary_en_possessive", "lowercase", "asciifolding", "dictionary_en_stemmer"]
+				},
+				"dictionary_es": {
+					"type": "custom",
+					"tokenizer": "standard",
+					"filter": ["lowercase", "asciifolding", "dictionary_es_stemmer"]
+				},
+				"dictionary_pt": {
+					"type": "custom",
+					"tokenizer": "standard",
+					"filter": ["lowercase", "asciifolding", "dictionary_pt_stemmer"]
+				}
+			}
+		}
+	})
+}
+
+//	Language of an index name, as `es` for `window_es_mx`, `menu_item_es_mx_<code>`
+//	or `window_es_mx_v2`
+pub fn index_language(_index_name: &str) -> Option<String> {
+	let index_name: String = _index_name.to_lowercase();
+	// the longest base name, `menu_item` and not `menu`
+	let base_name: &str = DICTIONARY_INDICES.iter()
+		.filter(|base_name| index_name.starts_with(&format!("{}_", base_name)))
+		.max_by_key(|base_name| base_name.len())?
+	;
+	index_name[base_name.len() + 1..]
+		.split('_')
+		.next()
+		.filter(|language| !language.is_empty())
+		.map(|language| language.to_owned())
+}
+
+//	Analyzer for text fields of an index, chosen from the index language
+pub fn index_analyzer(_index_name: &str) -> String {
+	match index_language(_index_name) {
+		Some(language) if ANALYZER_LANGUAGES.contains(&language.as_str()) => format!("dictionary_{}", language),
+		_ => STANDARD_ANALYZER.to_owned()
+	}
+}
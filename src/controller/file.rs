@@ -0,0 +1,310 @@
+use std::collections::HashMap;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use async_trait::async_trait;
+use serde_json::{json, Value};
+use tokio::sync::Mutex;
+
+use crate::controller::memory::{MemoryIndex, MemoryRecord, MemoryStore};
+use crate::controller::opensearch::{patch_fields, IndexDocument};
+use crate::controller::search::SearchHits;
+use crate::controller::store::{DictionaryStore, StoreHealth};
+use crate::error::DictionaryError;
+
+//	Dictionary store persisted on local disk, each index is a directory with
+//	a JSON file by document, as `<path>/window_es_mx/<id>.json`. Documents are
+//	loaded on startup and searched in memory
+pub struct FileStore {
+	path: PathBuf,
+	memory: MemoryStore,
+	//	Writes of memory and disk must be in the same order, the memory is
+	//	changed after the disk
+	write_lock: Mutex<()>,
+}
+
+impl FileStore {
+	pub fn new(_path: &str) -> Self {
+		let path: PathBuf = PathBuf::from(_path);
+		if let Err(error) = fs::create_dir_all(&path) {
+			log::error!("Error creating storage directory {:?}: {}", path, error);
+		}
+		let indices: HashMap<String, MemoryIndex> = load_indices(&path);
+		log::info!("File storage {:?} loaded with {} index(es)", path, indices.len());
+		Self {
+			path,
+			memory: MemoryStore::from_indices(indices),
+			write_lock: Mutex::new(()),
+		}
+	}
+
+	fn index_path(&self, _index_name: &str) -> PathBuf {
+		self.path.join(encode_file_name(_index_name))
+	}
+
+	fn document_path(&self, _index_name: &str, _id: &str) -> PathBuf {
+		self.index_path(_index_name).join(format!("{}.json", encode_file_name(_id)))
+	}
+}
+
+//	Keep file names valid on any file system, other characters are written as `%XX`
+fn encode_file_name(_name: &str) -> String {
+	let mut file_name: String = String::new();
+	for byte in _name.bytes() {
+		if byte.is_ascii_alphanumeric() || byte == b'_' || byte == b'-' {
+			file_name.push(byte as char);
+		} else {
+			file_name.push_str(&format!("%{:02X}", byte));
+		}
+	}
+	file_name
+}
+
+fn decode_file_name(_file_name: &str) -> String {
+	let bytes: &[u8] = _file_name.as_bytes();
+	let mut decoded: Vec<u8> = Vec::with_capacity(bytes.len());
+	let mut index: usize = 0;
+	while index < bytes.len() {
+		if bytes[index] == b'%' {
+			if let Some(byte) = _file_name.get(index + 1..index + 3).and_then(|hex| u8::from_str_radix(hex, 16).ok()) {
+				decoded.push(byte);
+				index += 3;
+				continue;
+			}
+		}
+		decoded.push(bytes[index]);
+		index += 1;
+	}
+	String::from_utf8_lossy(&decoded).to_string()
+}
+
+fn load_indices(_path: &Path) -> HashMap<String, MemoryIndex> {
+	let mut indices: HashMap<String, MemoryIndex> = HashMap::new();
+	let entries = match fs::read_dir(_path) {
+		Ok(value) => value,
+		Err(error) => {
+			log::error!("Error reading storage directory {:?}: {}", _path, error);
+			return indices;
+		}
+	};
+	for entry in entries.flatten() {
+		let index_path: PathBuf = entry.path();
+		if !index_path.is_dir() {
+			continue;
+		}
+		let index_name: String = decode_file_name(&entry.file_name().to_string_lossy());
+		let index: MemoryIndex = load_index(&index_path);
+		log::debug!("Index {:?} loaded with {} document(s)", index_name, index.len());
+		indices.insert(index_name, index);
+	}
+	indices
+}
+
+fn load_index(_path: &Path) -> MemoryIndex {
+	let mut index: MemoryIndex = MemoryIndex::new();
+	let entries = match fs::read_dir(_path) {
+		Ok(value) => value,
+		Err(error) => {
+			log::error!("Error reading index directory {:?}: {}", _path, error);
+			return index;
+		}
+	};
+	for entry in entries.flatten() {
+		let file_name: String = entry.file_name().to_string_lossy().to_string();
+		let id: &str = match file_name.strip_suffix(".json") {
+			Some(value) => value,
+			// as temporary files of an interrupted write
+			None => continue
+		};
+		let content: Value = match fs::read(entry.path()).map(|bytes| serde_json::from_slice::<Value>(&bytes)) {
+			Ok(Ok(value)) => value,
+			Ok(Err(error)) => {
+				log::error!("Invalid document file {:?}: {}", entry.path(), error);
+				continue;
+			},
+			Err(error) => {
+				log::error!("Error reading document file {:?}: {}", entry.path(), error);
+				continue;
+			}
+		};
+		index.insert(decode_file_name(id), MemoryRecord {
+			source: content["source"].to_owned(),
+			version: content["version"].as_i64(),
+		});
+	}
+	index
+}
+
+//	Run the blocking file operations out of the async runtime workers
+async fn run_blocking<F>(_operation: F) -> Result<(), DictionaryError>
+where
+	F: FnOnce() -> Result<(), DictionaryError> + Send + 'static
+{
+	match tokio::task::spawn_blocking(_operation).await {
+		Ok(result) => result,
+		Err(error) => Err(DictionaryError::BackendUnavailable(format!("Error running file operation: {}", error)))
+	}
+}
+
+//	Flush the entries of a directory, as a created, renamed or removed file
+fn sync_directory(_path: &Path) -> std::io::Result<()> {
+	fs::File::open(_path)?.sync_all()
+}
+
+fn sync_parent(_path: &Path) -> std::io::Result<()> {
+	match _path.parent() {
+		Some(parent) => sync_directory(parent),
+		None => Ok(())
+	}
+}
+
+fn write_file(_path: &Path, _bytes: &[u8]) -> std::io::Result<()> {
+	let mut file: fs::File = fs::File::create(_path)?;
+	file.write_all(_bytes)?;
+	file.sync_all()
+}
+
+//	Write to a temporary file and rename it, a document file is never half
+//	written. The file and its directory are flushed to disk before returning
+fn write_document(_path: &Path, _source: &Value, _version: Option<i64>) -> Result<(), DictionaryError> {
+	let content: Value = json!({
+		"version": _version,
+		"source": _source
+	});
+	let temporary_path: PathBuf = _path.with_extension("json.tmp");
+	let result: std::io::Result<()> = serde_json::to_vec(&content)
+		.map_err(std::io::Error::other)
+		.and_then(|bytes| write_file(&temporary_path, &bytes))
+		.and_then(|_| fs::rename(&temporary_path, _path))
+		.and_then(|_| sync_parent(_path))
+	;
+	if let Err(error) = result {
+		log::error!("Error writing document file {:?}: {}", _path, error);
+		return Err(DictionaryError::BackendUnavailable(format!("Error writing document file {:?}: {}", _path, error)));
+	}
+	Ok(())
+}
+
+#[async_trait]
+impl DictionaryStore for FileStore {
+	async fn health(&self) -> StoreHealth {
+		StoreHealth::local("file")
+	}
+
+	async fn create_index_definition(&self, _index: &dyn IndexDocument) -> Result<bool, DictionaryError> {
+		let index_name: String = _index.index_name();
+		let _guard = self.write_lock.lock().await;
+		if self.memory.exists_index(&index_name) {
+			return Ok(true);
+		}
+		let index_path: PathBuf = self.index_path(&index_name);
+		let name: String = index_name.to_owned();
+		run_blocking(move || {
+			fs::create_dir_all(&index_path)
+				.and_then(|_| sync_parent(&index_path))
+				.map_err(|error| DictionaryError::BackendUnavailable(format!("Error creating index {:?}: {}", name, error)))
+		}).await?;
+		if self.memory.create_index(&index_name) {
+			log::info!("Index created: {:?}", index_name);
+		}
+		Ok(true)
+	}
+
+	async fn delete_index_definition(&self, _index: &dyn IndexDocument) -> Result<bool, DictionaryError> {
+		let index_name: String = _index.index_name();
+		let _guard = self.write_lock.lock().await;
+		let index_path: PathBuf = self.index_path(&index_name);
+		let name: String = index_name.to_owned();
+		run_blocking(move || {
+			if !index_path.exists() {
+				return Ok(());
+			}
+			fs::remove_dir_all(&index_path)
+				.and_then(|_| sync_parent(&index_path))
+				.map_err(|error| DictionaryError::BackendUnavailable(format!("Error deleting index {:?}: {}", name, error)))
+		}).await?;
+		self.memory.delete_index(&index_name);
+		log::info!("Index deleted: {:?}", index_name);
+		Ok(true)
+	}
+
+	async fn get_alias_index(&self, _alias: String) -> Result<Option<String>, DictionaryError> {
+		self.memory.get_alias_index(_alias).await
+	}
+
+	async fn create(&self, _document: &dyn IndexDocument) -> Result<bool, DictionaryError> {
+		self.create_index_definition(_document).await?;
+		let index_name: String = _document.index_name();
+		let id: String = _document.id();
+		let source: Value = _document.data();
+		let version: Option<i64> = _document.version();
+		let _guard = self.write_lock.lock().await;
+		if !self.memory.is_current(&index_name, &id, version) {
+			log::warn!("Stale record rejected {:?} {:?}, version {:?} is not newer than stored version", index_name, id, version);
+			return Ok(false);
+		}
+		let document_path: PathBuf = self.document_path(&index_name, &id);
+		let content: Value = source.to_owned();
+		run_blocking(move || write_document(&document_path, &content, version)).await?;
+		self.memory.put(&index_name, id, source, version);
+		Ok(true)
+	}
+
+	async fn patch(&self, _document: &dyn IndexDocument) -> Result<bool, DictionaryError> {
+		let index_name: String = _document.index_name();
+		let id: String = _document.id();
+		let _guard = self.write_lock.lock().await;
+		let record: MemoryRecord = match self.memory.patched(&index_name, &id, patch_fields(_document), _document.version()) {
+			Some(value) => value,
+			None => {
+				log::warn!("Patch rejected {:?} {:?}, record not found or version {:?} is not newer than stored version", index_name, id, _document.version());
+				return Ok(false);
+			}
+		};
+		let document_path: PathBuf = self.document_path(&index_name, &id);
+		let (content, version): (Value, Option<i64>) = (record.source.to_owned(), record.version);
+		run_blocking(move || write_document(&document_path, &content, version)).await?;
+		self.memory.put(&index_name, id, record.source, record.version);
+		Ok(true)
+	}
+
+	async fn delete(&self, _document: &dyn IndexDocument) -> Result<bool, DictionaryError> {
+		let index_name: String = _document.index_name();
+		let id: String = _document.id();
+		let _guard = self.write_lock.lock().await;
+		if !self.memory.is_current_delete(&index_name, &id, _document.version()) {
+			log::warn!("Stale delete rejected {:?} {:?}, version {:?} is older than stored version", index_name, id, _document.version());
+			return Ok(false);
+		}
+		let document_path: PathBuf = self.document_path(&index_name, &id);
+		run_blocking(move || {
+			if !document_path.exists() {
+				return Ok(());
+			}
+			if let Err(error) = fs::remove_file(&document_path).and_then(|_| sync_parent(&document_path)) {
+				log::error!("Error deleting document file {:?}: {}", document_path, error);
+				return Err(DictionaryError::BackendUnavailable(format!("Error deleting document file {:?}: {}", document_path, error)));
+			}
+			Ok(())
+		}).await?;
+		self.memory.remove(&index_name, &id, _document.version());
+		Ok(true)
+	}
+
+	async fn get_by_id(&self, _document: &dyn IndexDocument) -> Result<Value, DictionaryError> {
+		self.memory.get_by_id(_document).await
+	}
+
+	async fn get_by_ids(&self, _index_name: String, _ids: &[String]) -> Result<Vec<Option<Value>>, DictionaryError> {
+		self.memory.get_by_ids(_index_name, _ids).await
+	}
+
+	async fn find_from_dsl_body(&self, _index_name: String, _body: Value, _from: i64, _size: i64) -> Result<Vec<Value>, DictionaryError> {
+		self.memory.find_from_dsl_body(_index_name, _body, _from, _size).await
+	}
+
+	async fn find_hits_from_dsl_body(&self, _index_name: String, _body: Value, _from: i64, _size: i64) -> Result<SearchHits, DictionaryError> {
+		self.memory.find_hits_from_dsl_body(_index_name, _body, _from, _size).await
+	}
+}
@@ -0,0 +1,227 @@
+use std::env;
+use std::str::FromStr;
+
+use jsonwebtoken::{decode, Algorithm, AlgorithmFamily, DecodingKey, Validation};
+use rdkafka::message::{Headers, Message};
+use serde_json::Value;
+
+use crate::controller::topic::TopicError;
+
+//	Names of message headers, the values are UTF-8 text
+pub const TOKEN_HEADER: &str = "authorization";
+pub const TENANT_HEADER: &str = "tenant";
+pub const SCHEMA_VERSION_HEADER: &str = "schema_version";
+pub const TRACE_ID_HEADER: &str = "trace_id";
+
+//	Headers of a Kafka message
+#[derive(Debug, Clone, Default)]
+pub struct MessageHeaders {
+	//	Token without `Bearer ` prefix
+	pub token: Option<String>,
+	//	ADempiere instance or tenant that produced the message
+	pub tenant: Option<String>,
+	pub schema_version: Option<String>,
+	pub trace_id: Option<String>,
+}
+
+impl MessageHeaders {
+	pub fn from_message<M: Message>(_message: &M) -> Result<Self, TopicError> {
+		let mut headers: MessageHeaders = MessageHeaders::default();
+		let values = match _message.headers() {
+			Some(value) => value,
+			None => return Ok(headers)
+		};
+		for header in values.iter() {
+			let value: Option<String> = match header.value {
+				None => None,
+				Some(bytes) => match std::str::from_utf8(bytes) {
+					Ok(text) if !text.trim().is_empty() => Some(text.trim().to_owned()),
+					Ok(_) => None,
+					Err(error) => return Err(
+						TopicError::invalid_headers(format!("Invalid header {:?}: {}", header.key, error))
+					)
+				}
+			};
+			match header.key.to_lowercase().as_str() {
+				TOKEN_HEADER => headers.token = value.map(|token| match token.strip_prefix("Bearer ") {
+					Some(value) => value.trim().to_owned(),
+					None => token
+				}),
+				TENANT_HEADER => headers.tenant = value,
+				SCHEMA_VERSION_HEADER => headers.schema_version = value,
+				TRACE_ID_HEADER => headers.trace_id = value,
+				_ => {}
+			};
+		}
+		Ok(headers)
+	}
+
+	//	Trace of logs, `-` without trace id
+	pub fn trace(&self) -> &str {
+		self.trace_id.as_deref().unwrap_or("-")
+	}
+}
+
+//	Validation of a token signature and expiration, with the key of messages
+pub struct TokenValidator {
+	key: DecodingKey,
+	algorithm: Algorithm,
+}
+
+impl TokenValidator {
+	//	Validator with the key from environment, none without key
+	pub fn from_env() -> Result<Option<Self>, String> {
+		Ok(
+			token_key()?.map(|(key, algorithm)| TokenValidator {
+				key,
+				algorithm,
+			})
+		)
+	}
+
+	//	Claims of a valid token, the token is without `Bearer ` prefix
+	pub fn claims(&self, _token: &str) -> Result<Value, String> {
+		match decode::<Value>(_token, &self.key, &Validation::new(self.algorithm)) {
+			Ok(value) => Ok(value.claims),
+			Err(error) => Err(format!("Invalid token: {}", error))
+		}
+	}
+}
+
+//	Validation of message headers from environment, a message that fails the
+//	validation is rejected
+pub struct HeaderValidator {
+	//	Validation of the token, without key the token is not required
+	token: Option<TokenValidator>,
+	//	Claim of token with the tenant, the tenant header is not signed
+	tenant_claim: String,
+	//	Allowed tenants, empty allows any tenant
+	tenants: Vec<String>,
+	//	Supported schema versions, a message without version is accepted
+	schema_versions: Vec<String>,
+}
+
+fn get_env_list(_key: &str, _default_value: &str) -> Vec<String> {
+	let value: String = match env::var(_key) {
+		Ok(value) => value,
+		Err(_) => {
+			log::warn!("Variable `{}` Not found from enviroment, as default {:?}", _key, _default_value);
+			_default_value.to_owned()
+		}
+	};
+	value.split_whitespace()
+		.map(|item| item.to_owned())
+		.collect()
+}
+
+//	Key from `KAFKA_AUTH_SECRET` (shared secret) or `KAFKA_AUTH_PUBLIC_KEY_LOCATION`
+//	(PEM file), the algorithm of `KAFKA_AUTH_ALGORITHM` must match the key. An
+//	invalid key is an error, the messages can not be validated without it
+fn token_key() -> Result<Option<(DecodingKey, Algorithm)>, String> {
+	let secret: Option<String> = env::var("KAFKA_AUTH_SECRET").ok()
+		.filter(|value| !value.trim().is_empty())
+	;
+	let public_key_location: Option<String> = env::var("KAFKA_AUTH_PUBLIC_KEY_LOCATION").ok()
+		.filter(|value| !value.trim().is_empty())
+	;
+	if secret.is_none() && public_key_location.is_none() {
+		log::warn!("Variables `KAFKA_AUTH_SECRET` and `KAFKA_AUTH_PUBLIC_KEY_LOCATION` Not found from enviroment, token of messages is not validated and the admin API is disabled");
+		return Ok(None);
+	}
+	let default_algorithm: &str = if secret.is_some() { "HS256" } else { "RS256" };
+	let algorithm_name: String = match env::var("KAFKA_AUTH_ALGORITHM") {
+		Ok(value) => value.trim().to_uppercase(),
+		Err(_) => {
+			log::warn!("Variable `KAFKA_AUTH_ALGORITHM` Not found from enviroment, as default {}", default_algorithm);
+			default_algorithm.to_owned()
+		}
+	};
+	let algorithm: Algorithm = match Algorithm::from_str(&algorithm_name) {
+		Ok(value) => value,
+		Err(error) => return Err(format!("Invalid token algorithm {:?}: {}", algorithm_name, error))
+	};
+
+	let key: Result<DecodingKey, String> = match (algorithm.family(), secret, public_key_location) {
+		(AlgorithmFamily::Hmac, Some(secret), _) => Ok(DecodingKey::from_secret(secret.trim().as_bytes())),
+		(AlgorithmFamily::Hmac, None, _) => Err(format!("Algorithm {:?} requires `KAFKA_AUTH_SECRET`", algorithm_name)),
+		(_, _, None) => Err(format!("Algorithm {:?} requires `KAFKA_AUTH_PUBLIC_KEY_LOCATION`", algorithm_name)),
+		(family, _, Some(location)) => match std::fs::read(location.trim()) {
+			Ok(pem) => match family {
+				AlgorithmFamily::Rsa => DecodingKey::from_rsa_pem(&pem),
+				AlgorithmFamily::Ec => DecodingKey::from_ec_pem(&pem),
+				_ => DecodingKey::from_ed_pem(&pem),
+			}.map_err(|error| format!("Invalid public key {:?}: {}", location, error)),
+			Err(error) => Err(format!("Error reading public key {:?}: {}", location, error))
+		}
+	};
+	match key {
+		Ok(value) => {
+			log::info!("Token of messages and admin requests is validated with {:?}", algorithm);
+			Ok(Some((value, algorithm)))
+		},
+		Err(error) => {
+			log::error!("Token validation error: {}", error);
+			Err(error)
+		}
+	}
+}
+
+impl HeaderValidator {
+	pub fn from_env() -> Result<Self, String> {
+		Ok(HeaderValidator {
+			token: TokenValidator::from_env()?,
+			tenant_claim: match env::var("KAFKA_AUTH_TENANT_CLAIM") {
+				Ok(value) => value.trim().to_owned(),
+				Err(_) => {
+					log::warn!("Variable `KAFKA_AUTH_TENANT_CLAIM` Not found from enviroment, as default \"tenant\"");
+					"tenant".to_owned()
+				}
+			},
+			tenants: get_env_list("KAFKA_ALLOWED_TENANTS", ""),
+			schema_versions: get_env_list("KAFKA_SCHEMA_VERSIONS", "1"),
+		})
+	}
+
+	//	Check the token, tenant and schema version of a message
+	pub fn validate(&self, _headers: &MessageHeaders) -> Result<(), TopicError> {
+		// with token the tenant is taken from its claim, the header must match it
+		let mut tenant: Option<String> = _headers.tenant.to_owned();
+		if let Some(validator) = &self.token {
+			let token: &str = match &_headers.token {
+				Some(value) => value,
+				None => return Err(TopicError::unauthorized(format!("Header `{}` is mandatory", TOKEN_HEADER)))
+			};
+			let claims: Value = match validator.claims(token) {
+				Ok(value) => value,
+				Err(error) => return Err(TopicError::unauthorized(error))
+			};
+			let claim_tenant: Option<String> = match &claims[&self.tenant_claim] {
+				Value::String(value) => Some(value.to_owned()),
+				Value::Number(value) => Some(value.to_string()),
+				_ => None
+			};
+			if _headers.tenant.is_some() && _headers.tenant != claim_tenant {
+				return Err(
+					TopicError::unauthorized(format!("Tenant {:?} does not match the token claim `{}`", _headers.tenant.as_deref().unwrap_or_default(), self.tenant_claim))
+				);
+			}
+			tenant = claim_tenant;
+		}
+		if !self.tenants.is_empty() {
+			match &tenant {
+				None if self.token.is_some() => return Err(TopicError::unauthorized(format!("Token claim `{}` is mandatory", self.tenant_claim))),
+				None => return Err(TopicError::invalid_headers(format!("Header `{}` is mandatory", TENANT_HEADER))),
+				Some(tenant) if !self.tenants.contains(tenant) => return Err(
+					TopicError::unauthorized(format!("Tenant {:?} not allowed", tenant))
+				),
+				Some(_) => {}
+			};
+		}
+		if let Some(version) = &_headers.schema_version {
+			if !self.schema_versions.contains(version) {
+				return Err(TopicError::invalid_headers(format!("Schema version {:?} not supported", version)));
+			}
+		}
+		Ok(())
+	}
+}
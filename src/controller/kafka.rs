@@ -2,11 +2,17 @@ use rdkafka::config::RDKafkaLogLevel;
 use rdkafka::consumer::stream_consumer::StreamConsumer;
 use rdkafka::consumer::{BaseConsumer, Consumer, ConsumerContext, Rebalance};
 use rdkafka::error::KafkaResult;
+use rdkafka::message::{Header, OwnedHeaders};
+use rdkafka::producer::{FutureProducer, FutureRecord};
+use rdkafka::util::Timeout;
 use rdkafka::{ClientConfig, TopicPartitionList, ClientContext};
+use std::env;
 use std::thread;
 use std::time::Duration;
 use std::{io::Error, io::ErrorKind};
 
+use crate::controller::headers::TRACE_ID_HEADER;
+
 pub struct CustomContext;
 
 impl ClientContext for CustomContext {}
@@ -47,7 +53,63 @@ impl ConsumerContext for CustomContext {
 }
 
 // A type alias with your custom consumer can be created for convenience.
-type LoggingConsumer = StreamConsumer<CustomContext>;
+pub type LoggingConsumer = StreamConsumer<CustomContext>;
+
+//	Prefix of variables with librdkafka properties, as `KAFKA_CONFIG_SASL_MECHANISMS`
+//	for `sasl.mechanisms`
+const CONFIG_PREFIX: &str = "KAFKA_CONFIG_";
+
+//	Variables of security settings with its librdkafka property
+const SECURITY_VARIABLES: [(&str, &str); 8] = [
+	("KAFKA_SECURITY_PROTOCOL", "security.protocol"),
+	("KAFKA_SSL_CA_LOCATION", "ssl.ca.location"),
+	("KAFKA_SSL_CERTIFICATE_LOCATION", "ssl.certificate.location"),
+	("KAFKA_SSL_KEY_LOCATION", "ssl.key.location"),
+	("KAFKA_SSL_KEY_PASSWORD", "ssl.key.password"),
+	("KAFKA_SASL_MECHANISM", "sasl.mechanisms"),
+	("KAFKA_SASL_USERNAME", "sasl.username"),
+	("KAFKA_SASL_PASSWORD", "sasl.password"),
+];
+
+//	Property from a `KAFKA_CONFIG_*` variable, `_` is replaced by `.`
+fn config_property(_variable: &str) -> Option<String> {
+	let name: &str = _variable.strip_prefix(CONFIG_PREFIX)?;
+	if name.is_empty() {
+		return None;
+	}
+	Some(name.to_lowercase().replace("_", "."))
+}
+
+//	Values are not logged, they can be secrets
+fn is_secret_property(_property: &str) -> bool {
+	_property.contains("password") || _property.contains("secret") || _property.ends_with(".pem")
+}
+
+//	Set the security settings and the `KAFKA_CONFIG_*` overrides from
+//	environment, the overrides are applied last so they replace any value
+pub fn apply_env_config(_config: &mut ClientConfig) {
+	for (variable, property) in SECURITY_VARIABLES.iter() {
+		if let Ok(value) = env::var(variable) {
+			if !value.trim().is_empty() {
+				_config.set(*property, value.trim());
+				log::info!("Kafka property `{}` set from `{}`", property, variable);
+			}
+		}
+	}
+	let mut overrides: Vec<(String, String)> = env::vars()
+		.filter_map(|(variable, value)| config_property(&variable).map(|property| (property, value)))
+		.collect()
+	;
+	overrides.sort();
+	for (property, value) in overrides {
+		if is_secret_property(&property) {
+			log::info!("Kafka property `{}` set from environment", property);
+		} else {
+			log::info!("Kafka property `{}` = {:?} set from environment", property, value);
+		}
+		_config.set(property, value);
+	}
+}
 
 pub fn create_consumer(
 	brokers: &str,
@@ -62,11 +124,10 @@ pub fn create_consumer(
 			.set("group.id", group_id)
 			.set("bootstrap.servers", brokers)
 			.set("enable.partition.eof", "false")
-			// TODO: Add support to dynamic SSL
-			// .set("security.protocol", "ssl")
-			// .set("ssl.ca.location", "/path/to/ca.pem")
 			.set("session.timeout.ms", "6000")
-			.set("enable.auto.commit", "true")
+			.set("enable.auto.commit", "false")
+			// offsets are committed after the messages are written
+			.set("enable.auto.offset.store", "false")
 			.set("message.max.bytes", "1000000000")
 			.set("message.copy.max.bytes", "1000000000")
 			.set("receive.message.max.bytes", "2147483647")
@@ -80,6 +141,7 @@ pub fn create_consumer(
 			.set("auto.offset.reset", "earliest")
 			.set_log_level(RDKafkaLogLevel::Debug)
 		;
+		apply_env_config(&mut config);
 		config
 	};
 
@@ -112,20 +174,129 @@ pub fn create_consumer(
 	Ok(consumer)
 }
 
+//	Reason of a message sent to dead letter topic
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DeadLetterKind {
+	//	Payload or key can not be parsed
+	Deserialization,
+	UnsupportedEvent,
+	//	Token or tenant of headers is not valid
+	Unauthorized,
+	//	Headers can not be parsed or are not supported, as schema version
+	InvalidHeaders,
+	//	Document was not written after the retries
+	Indexing,
+}
+
+impl DeadLetterKind {
+	pub fn code(&self) -> &'static str {
+		match self {
+			DeadLetterKind::Deserialization => "deserialization",
+			DeadLetterKind::UnsupportedEvent => "unsupported_event",
+			DeadLetterKind::Unauthorized => "unauthorized",
+			DeadLetterKind::InvalidHeaders => "invalid_headers",
+			DeadLetterKind::Indexing => "indexing",
+		}
+	}
+}
+
+//	Original message with the reason of failure
+pub struct DeadLetter<'a> {
+	pub topic: &'a str,
+	pub partition: i32,
+	pub offset: i64,
+	pub key: Option<&'a [u8]>,
+	pub payload: Option<&'a [u8]>,
+	pub kind: DeadLetterKind,
+	pub message: String,
+	//	Trace id of original message, it is kept in the same header
+	pub trace_id: Option<&'a str>,
+}
+
+//	Producer of messages that can not be processed, they are kept with the
+//	original key and payload to be inspected or replayed
+pub struct DeadLetterProducer {
+	producer: FutureProducer,
+	topic: String,
+}
+
+impl DeadLetterProducer {
+	pub fn new(brokers: &str, topic: &str) -> Result<Self, Error> {
+		let mut config: ClientConfig = ClientConfig::new();
+		config
+			.set("bootstrap.servers", brokers)
+			.set("message.timeout.ms", "30000")
+			.set("message.max.bytes", "1000000000")
+		;
+		apply_env_config(&mut config);
+		let producer: FutureProducer = match config.create() {
+			Ok(producer) => producer,
+			Err(e) => {
+				log::error!("Failed to create Kafka dead letter producer: {}", e);
+				return Err(Error::other(format!("Kafka error: {}", e)));
+			}
+		};
+		log::info!("Kafka dead letter topic: {}", topic);
+		Ok(DeadLetterProducer {
+			producer,
+			topic: topic.to_owned(),
+		})
+	}
+
+	pub fn topic(&self) -> &str {
+		&self.topic
+	}
+
+	pub async fn send(&self, _letter: &DeadLetter<'_>) -> Result<(), Error> {
+		let partition: String = _letter.partition.to_string();
+		let offset: String = _letter.offset.to_string();
+		let headers: OwnedHeaders = OwnedHeaders::new()
+			.insert(Header { key: "dlq.topic", value: Some(_letter.topic) })
+			.insert(Header { key: "dlq.partition", value: Some(&partition) })
+			.insert(Header { key: "dlq.offset", value: Some(&offset) })
+			.insert(Header { key: "dlq.error_kind", value: Some(_letter.kind.code()) })
+			.insert(Header { key: "dlq.error_message", value: Some(&_letter.message) })
+		;
+		let headers: OwnedHeaders = match _letter.trace_id {
+			Some(trace_id) => headers.insert(Header { key: TRACE_ID_HEADER, value: Some(trace_id) }),
+			None => headers
+		};
+		let mut record: FutureRecord<'_, [u8], [u8]> = FutureRecord::to(&self.topic)
+			.headers(headers)
+		;
+		if let Some(key) = _letter.key {
+			record = record.key(key);
+		}
+		if let Some(payload) = _letter.payload {
+			record = record.payload(payload);
+		}
+		match self.producer.send(record, Timeout::After(Duration::from_secs(30))).await {
+			Ok(_) => {
+				log::warn!("Message sent to dead letter topic {:?} (topic {:?}, partition {}, offset {}, trace {}): {} {}", self.topic, _letter.topic, _letter.partition, _letter.offset, _letter.trace_id.unwrap_or("-"), _letter.kind.code(), _letter.message);
+				Ok(())
+			},
+			Err((e, _)) => {
+				log::error!("Error sending message to dead letter topic {:?} (topic {:?}, partition {}, offset {}, trace {}): {}", self.topic, _letter.topic, _letter.partition, _letter.offset, _letter.trace_id.unwrap_or("-"), e);
+				Err(Error::other(format!("Kafka error: {}", e)))
+			}
+		}
+	}
+}
+
+//	Dead letter producer from `KAFKA_DEAD_LETTER_TOPIC`, it is disabled when empty.
+//	An error creating the producer of a configured topic is returned, the
+//	messages must not be skipped without it
+pub fn create_dead_letter_producer(brokers: &str) -> Result<Option<DeadLetterProducer>, Error> {
+	let topic: String = match std::env::var("KAFKA_DEAD_LETTER_TOPIC") {
+		Ok(value) => value.trim().to_owned(),
+		Err(_) => {
+			log::warn!("Variable `KAFKA_DEAD_LETTER_TOPIC` Not found from enviroment, dead letter topic is disabled");
+			"".to_owned()
+		}
+	};
+	if topic.is_empty() {
+		return Ok(None);
+	}
+	DeadLetterProducer::new(brokers, &topic).map(Some)
+}
 
-// pub fn create_consumer_with_config(
-// 	brokers: &str,
-// 	group_id: &str,
-// 	topics: &[&str],
-// 	config_overrides: &[(&str, &str)],
-// ) -> Result<LoggingConsumer, Error> {
-//     let mut consumer = create_consumer(brokers, group_id, topics)?;
-// 	for (key, value) in config_overrides {
-// 		match consumer.set_config_option(key, value) {
-// 			Ok(_) => log::info!("Set Kafka consumer config option: {} = {}", key, value),
-// 			Err(e) => log::warn!("Failed to set Kafka consumer config option {}: {}", key, e),
-// 		}
-// 	}
-
-// 	Ok(consumer)
-// }
@@ -0,0 +1,215 @@
+use serde::Serialize;
+use serde_json::{json, Map, Value};
+use opensearch::OpenSearch;
+
+use crate::controller::analysis::ANALYZER_LANGUAGES;
+use crate::controller::opensearch::{get_mappings, put_index_template, put_mapping, IndexDocument};
+use crate::models::{index_document_from_name, DICTIONARY_INDICES};
+use crate::error::DictionaryError;
+
+#[derive(Serialize, Debug, Clone)]
+pub struct MappingDrift {
+	//	Alias used by readers and writers
+	pub index: String,
+	pub physical_index: String,
+	//	Fields (as `name.keyword`) defined on code but missing on stored mapping, they can be added
+	pub added_fields: Vec<String>,
+	//	Fields with a different definition, as `name.analyzer`, they require a reindex
+	pub changed_fields: Vec<String>,
+	pub is_applied: bool,
+	pub is_reindex_required: bool,
+}
+
+//	Alias of a physical index, `window_es_mx_v2` is `window_es_mx`
+pub fn alias_from_index(_physical_index_name: &str) -> String {
+	if let Some((alias, version)) = _physical_index_name.rsplit_once("_v") {
+		if !version.is_empty() && version.chars().all(|character| character.is_ascii_digit()) {
+			return alias.to_owned();
+		}
+	}
+	_physical_index_name.to_owned()
+}
+
+fn compare_properties(
+	_path: &str,
+	_expected: &Map<String, Value>,
+	_stored: Option<&Map<String, Value>>,
+	_added_fields: &mut Vec<String>,
+	_changed_fields: &mut Vec<String>
+) {
+	for (name, expected_field) in _expected {
+		let path: String = if _path.is_empty() {
+			name.to_owned()
+		} else {
+			format!("{}.{}", _path, name)
+		};
+		match _stored.and_then(|stored| stored.get(name)) {
+			Some(stored_field) => compare_field(&path, expected_field, stored_field, _added_fields, _changed_fields),
+			None => _added_fields.push(path)
+		}
+	}
+}
+
+fn compare_field(
+	_path: &str,
+	_expected: &Value,
+	_stored: &Value,
+	_added_fields: &mut Vec<String>,
+	_changed_fields: &mut Vec<String>
+) {
+	let expected: &Map<String, Value> = match _expected.as_object() {
+		Some(value) => value,
+		None => return
+	};
+	for (key, expected_value) in expected {
+		if key == "properties" || key == "fields" {
+			// object properties or multi-fields
+			if let Some(expected_properties) = expected_value.as_object() {
+				compare_properties(_path, expected_properties, _stored[key].as_object(), _added_fields, _changed_fields);
+			}
+		} else if _stored.get(key) != Some(expected_value) {
+			_changed_fields.push(format!("{}.{}", _path, key));
+		}
+	}
+}
+
+//	Compare the mapping defined on code with the stored mapping, only fields
+//	defined on code are compared because stored mapping also has dynamic fields
+pub fn mapping_drift(
+	_physical_index_name: &str,
+	_expected_mapping: &Value,
+	_stored_mapping: &Value
+) -> MappingDrift {
+	let mut added_fields: Vec<String> = Vec::new();
+	let mut changed_fields: Vec<String> = Vec::new();
+	if let Some(expected_properties) = _expected_mapping["mappings"]["properties"].as_object() {
+		compare_properties("", expected_properties, _stored_mapping["mappings"]["properties"].as_object(), &mut added_fields, &mut changed_fields);
+	}
+	MappingDrift {
+		index: alias_from_index(_physical_index_name),
+		physical_index: _physical_index_name.to_owned(),
+		is_reindex_required: !changed_fields.is_empty(),
+		added_fields,
+		changed_fields,
+		is_applied: false,
+	}
+}
+
+//	Mapping with only top level fields that can be added without changing
+//	existing fields
+fn additive_mapping(_expected_mapping: &Value, _stored_mapping: &Value) -> Map<String, Value> {
+	let mut properties: Map<String, Value> = Map::new();
+	let expected_properties: &Map<String, Value> = match _expected_mapping["mappings"]["properties"].as_object() {
+		Some(value) => value,
+		None => return properties
+	};
+	let stored_properties: Option<&Map<String, Value>> = _stored_mapping["mappings"]["properties"].as_object();
+	for (name, expected_field) in expected_properties {
+		let mut added_fields: Vec<String> = Vec::new();
+		let mut changed_fields: Vec<String> = Vec::new();
+		match stored_properties.and_then(|stored| stored.get(name)) {
+			Some(stored_field) => compare_field(name, expected_field, stored_field, &mut added_fields, &mut changed_fields),
+			None => added_fields.push(name.to_owned())
+		}
+		if !added_fields.is_empty() && changed_fields.is_empty() {
+			properties.insert(name.to_owned(), expected_field.to_owned());
+		}
+	}
+	properties
+}
+
+//	Check the stored mapping of every dictionary index against the mapping
+//	defined on code, only indices with differences are returned. When
+//	`_apply` is true the new fields are added to stored mapping
+pub async fn check_mappings(_client: &OpenSearch, _apply: bool) -> Result<Vec<MappingDrift>, DictionaryError> {
+	let index_patterns: Vec<String> = DICTIONARY_INDICES.iter()
+		.map(|index_name| format!("{}*", index_name))
+		.collect()
+	;
+	let index_patterns: Vec<&str> = index_patterns.iter().map(|pattern| pattern.as_str()).collect();
+	let stored_mappings: Value = get_mappings(_client, &index_patterns).await?;
+	let stored_mappings: Map<String, Value> = match stored_mappings.as_object() {
+		Some(value) => value.to_owned(),
+		None => return Ok(Vec::new())
+	};
+
+	let mut drifts: Vec<MappingDrift> = Vec::new();
+	for (physical_index_name, stored_mapping) in stored_mappings {
+		let _document = match index_document_from_name(&alias_from_index(&physical_index_name)) {
+			Some(value) => value,
+			None => continue
+		};
+		let expected_mapping: Value = _document.mapping();
+		let mut drift: MappingDrift = mapping_drift(&physical_index_name, &expected_mapping, &stored_mapping);
+		if drift.added_fields.is_empty() && drift.changed_fields.is_empty() {
+			log::debug!("Mapping of {:?} is up to date", physical_index_name);
+			continue;
+		}
+		if drift.is_reindex_required {
+			log::warn!("Mapping of {:?} has incompatible changes {:?}, it requires a reindex of {:?}", physical_index_name, drift.changed_fields, drift.index);
+		}
+		if !drift.added_fields.is_empty() {
+			log::info!("Mapping of {:?} without fields {:?}", physical_index_name, drift.added_fields);
+			if _apply {
+				let properties: Map<String, Value> = additive_mapping(&expected_mapping, &stored_mapping);
+				if !properties.is_empty() {
+					match put_mapping(_client, &physical_index_name, json!({ "properties": properties })).await {
+						Ok(_) => drift.is_applied = true,
+						Err(error) => log::error!("{}", error)
+					}
+				}
+			}
+		}
+		drifts.push(drift);
+	}
+	Ok(drifts)
+}
+
+//	Index template name of an entity, as `dictionary_window`
+pub fn index_template_name(_index_name: &str) -> String {
+	format!("dictionary_{}", _index_name)
+}
+
+//	Index template of an entity, the indices with language, client or
+//	dictionary code suffix (and their versions) get the same mappings and
+//	settings whatever is the document that creates them. Templates of a
+//	language (as `window_es`) use a higher priority than the entity template
+pub fn index_template(_index_name: &str, _priority: u64, _document: &dyn IndexDocument) -> Value {
+	json!({
+		"index_patterns": [
+			_index_name,
+			format!("{}_*", _index_name)
+		],
+		"priority": _priority,
+		"template": _document.mapping(),
+		"_meta": {
+			"managed_by": "dictionary_rs"
+		}
+	})
+}
+
+//	Create or update the index templates of every dictionary index, with a
+//	template by language with analyzer. It should be called on startup
+//	before any index is created
+pub async fn register_index_templates(_client: &OpenSearch) -> Result<usize, DictionaryError> {
+	let mut templates: usize = 0;
+	for index_name in DICTIONARY_INDICES {
+		let mut template_indices: Vec<(String, u64)> = vec![(index_name.to_owned(), 100)];
+		for language in ANALYZER_LANGUAGES {
+			template_indices.push((format!("{}_{}", index_name, language), 110));
+		}
+		for (template_index, priority) in template_indices {
+			let _document = match index_document_from_name(&template_index) {
+				Some(value) => value,
+				None => continue
+			};
+			put_index_template(
+				_client,
+				&index_template_name(&template_index),
+				index_template(&template_index, priority, _document.as_ref())
+			).await?;
+			templates += 1;
+		}
+	}
+	Ok(templates)
+}
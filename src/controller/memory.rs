@@ -0,0 +1,1126 @@
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::RwLock;
+
+use async_trait::async_trait;
+use serde_json::Value;
+
+use crate::controller::opensearch::{patch_fields, IndexDocument};
+use crate::controller::search::{SearchHit, SearchHits};
+use crate::controller::store::{DictionaryStore, StoreHealth};
+use crate::error::DictionaryError;
+
+#[derive(Debug, Clone)]
+pub struct MemoryRecord {
+	pub source: Value,
+	//	External version of document, as OpenSearch `external`
+	pub version: Option<i64>,
+}
+
+//	Documents by id, sorted to get the same order on every search
+pub type MemoryIndex = BTreeMap<String, MemoryRecord>;
+
+//	Dictionary store without external services, the searches are evaluated
+//	with a small subset of OpenSearch query DSL
+#[derive(Default)]
+pub struct MemoryStore {
+	indices: RwLock<HashMap<String, MemoryIndex>>,
+}
+
+impl MemoryStore {
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	pub fn from_indices(_indices: HashMap<String, MemoryIndex>) -> Self {
+		Self {
+			indices: RwLock::new(_indices),
+		}
+	}
+
+	pub fn create_index(&self, _index_name: &str) -> bool {
+		let mut indices = self.write();
+		if indices.contains_key(_index_name) {
+			return false;
+		}
+		indices.insert(_index_name.to_owned(), MemoryIndex::new());
+		true
+	}
+
+	pub fn delete_index(&self, _index_name: &str) -> bool {
+		self.write().remove(_index_name).is_some()
+	}
+
+	pub fn exists_index(&self, _index_name: &str) -> bool {
+		self.read().contains_key(_index_name)
+	}
+
+	//	Put a document, a version not newer than the stored one is rejected with `false`
+	pub fn put(&self, _index_name: &str, _id: String, _source: Value, _version: Option<i64>) -> bool {
+		let mut indices = self.write();
+		let index: &mut MemoryIndex = indices.entry(_index_name.to_owned()).or_default();
+		if is_stale(index.get(&_id), _version) {
+			return false;
+		}
+		index.insert(_id, MemoryRecord {
+			source: _source,
+			version: _version,
+		});
+		true
+	}
+
+	//	Replace the fields of a stored document, the patched record is returned
+	//	and none when it does not exist or the version is not newer than the stored one
+	pub fn patch(&self, _index_name: &str, _id: &str, _fields: Value, _version: Option<i64>) -> Option<MemoryRecord> {
+		let mut indices = self.write();
+		let record: &mut MemoryRecord = indices.get_mut(_index_name)?.get_mut(_id)?;
+		if is_stale(Some(record), _version) {
+			return None;
+		}
+		*record = patch_record(record, _fields, _version);
+		Some(record.to_owned())
+	}
+
+	//	Record of a patch without changing the stored document, none when it does
+	//	not exist or the version is not newer than the stored one
+	pub fn patched(&self, _index_name: &str, _id: &str, _fields: Value, _version: Option<i64>) -> Option<MemoryRecord> {
+		let indices = self.read();
+		let record: &MemoryRecord = indices.get(_index_name)?.get(_id)?;
+		if is_stale(Some(record), _version) {
+			return None;
+		}
+		Some(patch_record(record, _fields, _version))
+	}
+
+	//	Check if a write of version is newer than the stored document
+	pub fn is_current(&self, _index_name: &str, _id: &str, _version: Option<i64>) -> bool {
+		let indices = self.read();
+		!is_stale(indices.get(_index_name).and_then(|index| index.get(_id)), _version)
+	}
+
+	//	Check if a delete of version is not older than the stored document
+	pub fn is_current_delete(&self, _index_name: &str, _id: &str, _version: Option<i64>) -> bool {
+		let indices = self.read();
+		!is_stale_delete(indices.get(_index_name).and_then(|index| index.get(_id)), _version)
+	}
+
+	//	Remove a document, a version older than the stored one is rejected with `false`
+	pub fn remove(&self, _index_name: &str, _id: &str, _version: Option<i64>) -> bool {
+		let mut indices = self.write();
+		let index: &mut MemoryIndex = match indices.get_mut(_index_name) {
+			Some(value) => value,
+			None => return true
+		};
+		if is_stale_delete(index.get(_id), _version) {
+			return false;
+		}
+		index.remove(_id);
+		true
+	}
+
+	pub fn get(&self, _index_name: &str, _id: &str) -> Option<Value> {
+		self.read()
+			.get(_index_name)
+			.and_then(|index| index.get(_id))
+			.map(|record| record.source.to_owned())
+	}
+
+	pub fn search(&self, _index_name: &str, _body: &Value, _from: i64, _size: i64) -> Result<Vec<Value>, DictionaryError> {
+		let result: SearchHits = self.search_hits(_index_name, _body, _from, _size)?;
+		Ok(result.hits.into_iter().map(|hit| hit.source).collect())
+	}
+
+	//	Matches of a search without score, the `highlight` of body is supported
+	//	for the words of query
+	pub fn search_hits(&self, _index_name: &str, _body: &Value, _from: i64, _size: i64) -> Result<SearchHits, DictionaryError> {
+		let indices = self.read();
+		let index: &MemoryIndex = match indices.get(_index_name) {
+			Some(value) => value,
+			None => return Err(DictionaryError::IndexMissing(format!("Index {:?} Not Found", _index_name)))
+		};
+		let query: &Value = match _body.get("query") {
+			Some(value) => value,
+			None => &Value::Null
+		};
+		let mut list: Vec<Value> = Vec::new();
+		for record in index.values() {
+			if matches_query(&record.source, query) {
+				list.push(record.source.to_owned());
+			}
+		}
+		if let Some(sort) = _body.get("sort") {
+			sort_values(&mut list, sort);
+		}
+		let words: Vec<String> = query_words(query);
+		let total: i64 = list.len() as i64;
+		let hits: Vec<SearchHit> = list.into_iter()
+				.skip(_from.max(0) as usize)
+				.take(_size.max(0) as usize)
+				.map(|source| {
+					let highlight: HashMap<String, Vec<String>> = match _body.get("highlight") {
+						Some(parameters) => highlight_fields(&source, parameters, &words),
+						None => HashMap::new()
+					};
+					SearchHit {
+						source,
+						score: None,
+						highlight,
+					}
+				})
+				.collect()
+		;
+		Ok(SearchHits {
+			total,
+			hits,
+		})
+	}
+
+	fn read(&self) -> std::sync::RwLockReadGuard<'_, HashMap<String, MemoryIndex>> {
+		match self.indices.read() {
+			Ok(value) => value,
+			Err(error) => error.into_inner()
+		}
+	}
+
+	fn write(&self) -> std::sync::RwLockWriteGuard<'_, HashMap<String, MemoryIndex>> {
+		match self.indices.write() {
+			Ok(value) => value,
+			Err(error) => error.into_inner()
+		}
+	}
+}
+
+fn patch_record(_record: &MemoryRecord, _fields: Value, _version: Option<i64>) -> MemoryRecord {
+	let mut record: MemoryRecord = _record.to_owned();
+	if let (Value::Object(source), Value::Object(fields)) = (&mut record.source, _fields) {
+		source.extend(fields);
+	}
+	if _version.is_some() {
+		record.version = _version;
+	}
+	record
+}
+
+//	A write must be newer than the stored document, as OpenSearch `external`
+fn is_stale(_stored: Option<&MemoryRecord>, _version: Option<i64>) -> bool {
+	match (_stored.and_then(|record| record.version), _version) {
+		(Some(stored_version), Some(version)) => version <= stored_version,
+		_ => false
+	}
+}
+
+//	A delete can have the same version of the stored document, as OpenSearch `external_gte`
+fn is_stale_delete(_stored: Option<&MemoryRecord>, _version: Option<i64>) -> bool {
+	match (_stored.and_then(|record| record.version), _version) {
+		(Some(stored_version), Some(version)) => version < stored_version,
+		_ => false
+	}
+}
+
+#[async_trait]
+impl DictionaryStore for MemoryStore {
+	async fn health(&self) -> StoreHealth {
+		StoreHealth::local("memory")
+	}
+
+	async fn create_index_definition(&self, _index: &dyn IndexDocument) -> Result<bool, DictionaryError> {
+		self.create_index(&_index.index_name());
+		Ok(true)
+	}
+
+	async fn delete_index_definition(&self, _index: &dyn IndexDocument) -> Result<bool, DictionaryError> {
+		self.delete_index(&_index.index_name());
+		Ok(true)
+	}
+
+	async fn get_alias_index(&self, _alias: String) -> Result<Option<String>, DictionaryError> {
+		if self.exists_index(&_alias) {
+			return Ok(Some(_alias));
+		}
+		Ok(None)
+	}
+
+	async fn create(&self, _document: &dyn IndexDocument) -> Result<bool, DictionaryError> {
+		let is_created: bool = self.put(&_document.index_name(), _document.id(), _document.data(), _document.version());
+		if !is_created {
+			log::warn!("Stale record rejected {:?} {:?}, version {:?} is not newer than stored version", _document.index_name(), _document.id(), _document.version());
+		}
+		Ok(is_created)
+	}
+
+	async fn patch(&self, _document: &dyn IndexDocument) -> Result<bool, DictionaryError> {
+		let is_patched: bool = self.patch(&_document.index_name(), &_document.id(), patch_fields(_document), _document.version()).is_some();
+		if !is_patched {
+			log::warn!("Patch rejected {:?} {:?}, record not found or version {:?} is not newer than stored version", _document.index_name(), _document.id(), _document.version());
+		}
+		Ok(is_patched)
+	}
+
+	async fn delete(&self, _document: &dyn IndexDocument) -> Result<bool, DictionaryError> {
+		let is_deleted: bool = self.remove(&_document.index_name(), &_document.id(), _document.version());
+		if !is_deleted {
+			log::warn!("Stale delete rejected {:?} {:?}, version {:?} is older than stored version", _document.index_name(), _document.id(), _document.version());
+		}
+		Ok(is_deleted)
+	}
+
+	async fn get_by_id(&self, _document: &dyn IndexDocument) -> Result<Value, DictionaryError> {
+		match self.get(&_document.index_name(), &_document.id()) {
+			Some(value) => Ok(value),
+			None => Err(DictionaryError::NotFound(format!("Error finding record by ID {:?} {:?}", _document.index_name(), _document.id())))
+		}
+	}
+
+	async fn get_by_ids(&self, _index_name: String, _ids: &[String]) -> Result<Vec<Option<Value>>, DictionaryError> {
+		if !self.exists_index(&_index_name) {
+			return Err(DictionaryError::IndexMissing(format!("Index {:?} Not Found", _index_name)));
+		}
+		Ok(
+			_ids.iter()
+				.map(|id| self.get(&_index_name, id))
+				.collect()
+		)
+	}
+
+	async fn find_from_dsl_body(&self, _index_name: String, _body: Value, _from: i64, _size: i64) -> Result<Vec<Value>, DictionaryError> {
+		self.search(&_index_name, &_body, _from, _size)
+	}
+
+	async fn find_hits_from_dsl_body(&self, _index_name: String, _body: Value, _from: i64, _size: i64) -> Result<SearchHits, DictionaryError> {
+		self.search_hits(&_index_name, &_body, _from, _size)
+	}
+}
+
+//	Sub fields of mappings, they have the same source value of their field
+const SUB_FIELDS: [&str; 5] = [".keyword", ".as_you_type", "._2gram", "._3gram", "._index_prefix"];
+
+//	Values of a field path as `action.keyword` or `tabs.name`, the values of
+//	arrays are flattened. The sub fields (as `keyword`) are the source value
+fn field_values<'a>(_source: &'a Value, _field: &str) -> Vec<&'a Value> {
+	let mut values: Vec<&'a Value> = vec![_source];
+	for name in _field.split('.') {
+		let mut next_values: Vec<&'a Value> = Vec::new();
+		for value in values {
+			match value {
+				Value::Object(map) => {
+					if let Some(child) = map.get(name) {
+						push_flatten(&mut next_values, child);
+					}
+				},
+				Value::Array(items) => {
+					for item in items {
+						if let Some(child) = item.get(name) {
+							push_flatten(&mut next_values, child);
+						}
+					}
+				},
+				_ => {}
+			}
+		}
+		values = next_values;
+	}
+	if values.is_empty() {
+		for sub_field in SUB_FIELDS {
+			if let Some(field) = _field.strip_suffix(sub_field) {
+				return field_values(_source, field);
+			}
+		}
+	}
+	values
+}
+
+fn push_flatten<'a>(_values: &mut Vec<&'a Value>, _value: &'a Value) {
+	match _value {
+		Value::Array(items) => {
+			for item in items {
+				push_flatten(_values, item);
+			}
+		},
+		Value::Null => {},
+		_ => _values.push(_value)
+	}
+}
+
+//	All text values of a document, used by searches without fields
+fn text_values<'a>(_value: &'a Value, _values: &mut Vec<&'a Value>) {
+	match _value {
+		Value::Object(map) => {
+			for child in map.values() {
+				text_values(child, _values);
+			}
+		},
+		Value::Array(items) => {
+			for item in items {
+				text_values(item, _values);
+			}
+		},
+		Value::String(_) => _values.push(_value),
+		_ => {}
+	}
+}
+
+fn search_fields<'a>(_source: &'a Value, _query: &Value) -> Vec<&'a Value> {
+	let mut fields: Vec<String> = Vec::new();
+	if let Some(list) = _query.get("fields").and_then(|value| value.as_array()) {
+		for field in list {
+			if let Some(name) = field.as_str() {
+				// without boost, as `name^3`
+				fields.push(name.split('^').next().unwrap_or(name).to_owned());
+			}
+		}
+	} else if let Some(field) = _query.get("default_field").and_then(|value| value.as_str()) {
+		fields.push(field.to_owned());
+	}
+	let mut values: Vec<&'a Value> = Vec::new();
+	if fields.is_empty() || fields.iter().any(|field| field == "*") {
+		text_values(_source, &mut values);
+		return values;
+	}
+	for field in fields {
+		values.extend(field_values(_source, &field));
+	}
+	values
+}
+
+fn value_to_text(_value: &Value) -> String {
+	match _value {
+		Value::String(text) => text.to_owned(),
+		_ => _value.to_string()
+	}
+}
+
+//	Lowercase words of a text, as the standard analyzer
+fn tokens(_text: &str) -> Vec<String> {
+	_text.split(|character: char| !character.is_alphanumeric())
+		.filter(|token| !token.is_empty())
+		.map(|token| token.to_lowercase())
+		.collect()
+}
+
+fn is_equal(_stored: &Value, _expected: &Value) -> bool {
+	match (_stored, _expected) {
+		(Value::Number(stored), Value::Number(expected)) => stored.as_f64() == expected.as_f64(),
+		(Value::String(stored), Value::Number(_)) | (Value::String(stored), Value::Bool(_)) => stored == &_expected.to_string(),
+		(Value::Number(_), Value::String(expected)) | (Value::Bool(_), Value::String(expected)) => &_stored.to_string() == expected,
+		_ => _stored == _expected
+	}
+}
+
+//	Character of pattern and its width, none is any character (`?`) and `\`
+//	escapes the next character
+fn pattern_char(_pattern: &[char], _index: usize) -> (Option<char>, usize) {
+	match _pattern[_index] {
+		'\\' if _index + 1 < _pattern.len() => (Some(_pattern[_index + 1]), 2),
+		'?' => (None, 1),
+		character => (Some(character), 1),
+	}
+}
+
+//	Match a text with `*` (any characters) and `?` (one character)
+fn wildcard_match(_pattern: &[char], _text: &[char]) -> bool {
+	let (mut pattern_index, mut text_index) = (0, 0);
+	let mut star_index: Option<usize> = None;
+	let mut match_index: usize = 0;
+	while text_index < _text.len() {
+		if pattern_index < _pattern.len() && _pattern[pattern_index] == '*' {
+			star_index = Some(pattern_index);
+			match_index = text_index;
+			pattern_index += 1;
+			continue;
+		}
+		if pattern_index < _pattern.len() {
+			let (character, width) = pattern_char(_pattern, pattern_index);
+			if character.is_none_or(|character| character == _text[text_index]) {
+				pattern_index += width;
+				text_index += 1;
+				continue;
+			}
+		}
+		match star_index {
+			Some(star) => {
+				pattern_index = star + 1;
+				match_index += 1;
+				text_index = match_index;
+			},
+			None => return false
+		}
+	}
+	_pattern[pattern_index..].iter().all(|character| *character == '*')
+}
+
+fn matches_wildcard(_values: &[&Value], _pattern: &str) -> bool {
+	let pattern: Vec<char> = _pattern.to_lowercase().chars().collect();
+	_values.iter().any(|value| {
+		let text: String = value_to_text(value).to_lowercase();
+		if wildcard_match(&pattern, &text.chars().collect::<Vec<char>>()) {
+			return true;
+		}
+		tokens(&text).iter().any(|token| wildcard_match(&pattern, &token.chars().collect::<Vec<char>>()))
+	})
+}
+
+//	Match the words of `_text` with the words of values, with `and` operator
+//	all words must be found
+fn matches_text(_values: &[&Value], _text: &str, _is_all: bool) -> bool {
+	matches_tokens(_values, _text, _is_all, |expected: &str, stored: &str, _| expected == stored)
+}
+
+//	Match the words of `_text` with `_is_token_match(expected, stored, is_last_word)`
+fn matches_tokens<F: Fn(&str, &str, bool) -> bool>(_values: &[&Value], _text: &str, _is_all: bool, _is_token_match: F) -> bool {
+	let expected_tokens: Vec<String> = tokens(_text);
+	if expected_tokens.is_empty() {
+		return false;
+	}
+	let stored_tokens: Vec<String> = _values.iter()
+		.flat_map(|value| tokens(&value_to_text(value)))
+		.collect()
+	;
+	let last_index: usize = expected_tokens.len() - 1;
+	let is_found = |(index, expected): (usize, &String)| {
+		stored_tokens.iter().any(|stored| _is_token_match(expected, stored, index == last_index))
+	};
+	if _is_all {
+		expected_tokens.iter().enumerate().all(is_found)
+	} else {
+		expected_tokens.iter().enumerate().any(is_found)
+	}
+}
+
+//	Edit distance between two words, as fuzzy queries a transposition of two
+//	adjacent characters is a single edit
+fn edit_distance(_first: &str, _second: &str) -> usize {
+	let first: Vec<char> = _first.chars().collect();
+	let second: Vec<char> = _second.chars().collect();
+	let mut distances: Vec<Vec<usize>> = vec![vec![0; second.len() + 1]; first.len() + 1];
+	for (first_index, row) in distances.iter_mut().enumerate() {
+		row[0] = first_index;
+	}
+	for (second_index, distance) in distances[0].iter_mut().enumerate() {
+		*distance = second_index;
+	}
+	for first_index in 1..=first.len() {
+		for second_index in 1..=second.len() {
+			let cost: usize = if first[first_index - 1] == second[second_index - 1] { 0 } else { 1 };
+			let mut distance: usize = (distances[first_index - 1][second_index - 1] + cost)
+				.min(distances[first_index - 1][second_index] + 1)
+				.min(distances[first_index][second_index - 1] + 1)
+			;
+			if first_index > 1 && second_index > 1 && first[first_index - 1] == second[second_index - 2] && first[first_index - 2] == second[second_index - 1] {
+				distance = distance.min(distances[first_index - 2][second_index - 2] + 1);
+			}
+			distances[first_index][second_index] = distance;
+		}
+	}
+	distances[first.len()][second.len()]
+}
+
+//	Allowed edits of a word, `AUTO` allows 1 edit from 3 characters and 2 from 6
+fn fuzziness_edits(_fuzziness: &Value, _word: &str) -> usize {
+	match _fuzziness.as_u64().or_else(|| _fuzziness.as_str().and_then(|value| value.parse::<u64>().ok())) {
+		Some(edits) => edits as usize,
+		None => match _word.chars().count() {
+			0..=2 => 0,
+			3..=5 => 1,
+			_ => 2
+		}
+	}
+}
+
+fn is_fuzzy_match(_expected: &str, _stored: &str, _fuzziness: &Value, _prefix_length: usize) -> bool {
+	if _expected == _stored {
+		return true;
+	}
+	let expected_prefix: String = _expected.chars().take(_prefix_length).collect();
+	if !_stored.starts_with(&expected_prefix) {
+		return false;
+	}
+	edit_distance(_expected, _stored) <= fuzziness_edits(_fuzziness, _expected)
+}
+
+//	Field name and parameters of a leaf query, as `{ "name": { "query": "..." } }`
+//	or the short form `{ "name": "..." }`
+fn leaf_query<'a>(_query: &'a Value, _parameter: &str) -> Option<(&'a String, &'a Value, &'a Value)> {
+	let (field, parameters) = _query.as_object()?.iter().next()?;
+	match parameters.get(_parameter) {
+		Some(value) if parameters.is_object() => Some((field, value, parameters)),
+		_ => Some((field, parameters, &Value::Null))
+	}
+}
+
+fn clauses(_value: Option<&Value>) -> Vec<&Value> {
+	match _value {
+		Some(Value::Array(items)) => items.iter().collect(),
+		Some(value) => vec![value],
+		None => Vec::new()
+	}
+}
+
+fn matches_bool(_source: &Value, _query: &Value) -> bool {
+	let must: Vec<&Value> = clauses(_query.get("must"));
+	let filter: Vec<&Value> = clauses(_query.get("filter"));
+	let must_not: Vec<&Value> = clauses(_query.get("must_not"));
+	let should: Vec<&Value> = clauses(_query.get("should"));
+	if !must.iter().chain(filter.iter()).all(|clause| matches_query(_source, clause)) {
+		return false;
+	}
+	if must_not.iter().any(|clause| matches_query(_source, clause)) {
+		return false;
+	}
+	if should.is_empty() {
+		return true;
+	}
+	let default_minimum: u64 = if must.is_empty() && filter.is_empty() { 1 } else { 0 };
+	let minimum: u64 = _query.get("minimum_should_match")
+		.and_then(|value| value.as_u64().or_else(|| value.as_str().and_then(|text| text.parse::<u64>().ok())))
+		.unwrap_or(default_minimum)
+	;
+	let matched: u64 = should.iter().filter(|clause| matches_query(_source, clause)).count() as u64;
+	matched >= minimum
+}
+
+fn compare_range(_stored: &Value, _limit: &Value) -> Option<Ordering> {
+	match (_stored.as_f64(), _limit.as_f64()) {
+		(Some(stored), Some(limit)) => stored.partial_cmp(&limit),
+		_ => Some(value_to_text(_stored).cmp(&value_to_text(_limit)))
+	}
+}
+
+fn matches_range(_values: &[&Value], _parameters: &Value) -> bool {
+	_values.iter().any(|value| {
+		let mut is_match: bool = true;
+		if let Some(limit) = _parameters.get("gt") {
+			is_match &= compare_range(value, limit) == Some(Ordering::Greater);
+		}
+		if let Some(limit) = _parameters.get("gte") {
+			is_match &= compare_range(value, limit) != Some(Ordering::Less);
+		}
+		if let Some(limit) = _parameters.get("lt") {
+			is_match &= compare_range(value, limit) == Some(Ordering::Less);
+		}
+		if let Some(limit) = _parameters.get("lte") {
+			is_match &= compare_range(value, limit) != Some(Ordering::Greater);
+		}
+		is_match
+	})
+}
+
+//	Evaluate a query over a document source, unsupported queries do not match
+pub fn matches_query(_source: &Value, _query: &Value) -> bool {
+	let (query_type, query) = match _query.as_object().and_then(|map| map.iter().next()) {
+		Some(value) => value,
+		// without query all documents match
+		None => return _query.is_null() || _query.is_object()
+	};
+	match query_type.as_str() {
+		"match_all" => true,
+		"match_none" => false,
+		"bool" => matches_bool(_source, query),
+		"term" => match leaf_query(query, "value") {
+			Some((field, value, _)) => field_values(_source, field).iter().any(|stored| is_equal(stored, value)),
+			None => false
+		},
+		"terms" => match query.as_object().and_then(|map| map.iter().find(|(name, _)| name.as_str() != "boost")) {
+			Some((field, Value::Array(expected))) => {
+				field_values(_source, field).iter().any(|stored| expected.iter().any(|value| is_equal(stored, value)))
+			},
+			_ => false
+		},
+		"ids" => match query.get("values").and_then(|value| value.as_array()) {
+			Some(ids) => ids.iter().any(|id| _source.get("id").is_some_and(|stored| is_equal(stored, id))),
+			None => false
+		},
+		"exists" => match query.get("field").and_then(|value| value.as_str()) {
+			Some(field) => !field_values(_source, field).is_empty(),
+			None => false
+		},
+		"range" => match query.as_object().and_then(|map| map.iter().next()) {
+			Some((field, parameters)) => matches_range(&field_values(_source, field), parameters),
+			None => false
+		},
+		"prefix" => match leaf_query(query, "value") {
+			Some((field, value, _)) => matches_wildcard(&field_values(_source, field), &format!("{}*", value_to_text(value))),
+			None => false
+		},
+		"wildcard" => match leaf_query(query, "value") {
+			Some((field, value, _)) => matches_wildcard(&field_values(_source, field), &value_to_text(value)),
+			None => false
+		},
+		"match" | "match_phrase" => match leaf_query(query, "query") {
+			Some((field, value, parameters)) => {
+				let is_all: bool = query_type == "match_phrase" || parameters.get("operator").and_then(|value| value.as_str()).is_some_and(|operator| operator.eq_ignore_ascii_case("and"));
+				let values: Vec<&Value> = field_values(_source, field);
+				match parameters.get("fuzziness") {
+					Some(fuzziness) => {
+						let prefix_length: usize = parameters.get("prefix_length").and_then(|value| value.as_u64()).unwrap_or(0) as usize;
+						matches_tokens(&values, &value_to_text(value), is_all, |expected: &str, stored: &str, _| {
+							is_fuzzy_match(expected, stored, fuzziness, prefix_length)
+						})
+					},
+					None => matches_text(&values, &value_to_text(value), is_all)
+				}
+			},
+			None => false
+		},
+		"multi_match" => {
+			let text: String = query.get("query").map(value_to_text).unwrap_or_default();
+			let values: Vec<&Value> = search_fields(_source, query);
+			if query.get("type").and_then(|value| value.as_str()) == Some("bool_prefix") {
+				// the last word can be incomplete
+				return matches_tokens(&values, &text, true, |expected: &str, stored: &str, is_last: bool| {
+					expected == stored || (is_last && stored.starts_with(expected))
+				});
+			}
+			let is_all: bool = query.get("operator").and_then(|value| value.as_str()).is_some_and(|operator| operator.eq_ignore_ascii_case("and"));
+			matches_text(&values, &text, is_all)
+		},
+		"query_string" | "simple_query_string" => {
+			let text: String = query.get("query").map(value_to_text).unwrap_or_default();
+			let is_all: bool = query.get("default_operator").and_then(|value| value.as_str()).is_some_and(|operator| operator.eq_ignore_ascii_case("and"));
+			let values: Vec<&Value> = search_fields(_source, query);
+			let terms: Vec<&str> = text.split_whitespace()
+				.filter(|term| *term != "OR" && *term != "AND")
+				.collect()
+			;
+			if terms.is_empty() {
+				return false;
+			}
+			let is_found = |term: &&str| {
+				if term.contains('*') || term.contains('?') {
+					matches_wildcard(&values, term)
+				} else {
+					matches_text(&values, term, true)
+				}
+			};
+			if is_all {
+				terms.iter().all(is_found)
+			} else {
+				terms.iter().any(is_found)
+			}
+		},
+		_ => {
+			log::warn!("Unsupported query {:?} on memory store", query_type);
+			false
+		}
+	}
+}
+
+fn compare_sort_values(_first: Option<&&Value>, _second: Option<&&Value>) -> Ordering {
+	match (_first, _second) {
+		(Some(first), Some(second)) => match (first.as_f64(), second.as_f64()) {
+			(Some(first), Some(second)) => first.partial_cmp(&second).unwrap_or(Ordering::Equal),
+			_ => value_to_text(first).cmp(&value_to_text(second))
+		},
+		// missing values are sorted last
+		(Some(_), None) => Ordering::Less,
+		(None, Some(_)) => Ordering::Greater,
+		(None, None) => Ordering::Equal
+	}
+}
+
+//	Sort as `[{ "sequence": { "order": "asc" } }, "name.keyword"]`, the score is ignored
+fn sort_values(_values: &mut [Value], _sort: &Value) {
+	let mut fields: Vec<(String, bool)> = Vec::new();
+	for sort in clauses(Some(_sort)) {
+		match sort {
+			Value::String(field) => fields.push((field.to_owned(), false)),
+			Value::Object(map) => {
+				for (field, order) in map {
+					let order: &str = order.get("order").and_then(|value| value.as_str()).or(order.as_str()).unwrap_or("asc");
+					fields.push((field.to_owned(), order.eq_ignore_ascii_case("desc")));
+				}
+			},
+			_ => {}
+		}
+	}
+	fields.retain(|(field, _)| field != "_score");
+	_values.sort_by(|first, second| {
+		for (field, is_descending) in &fields {
+			let first_values: Vec<&Value> = field_values(first, field);
+			let second_values: Vec<&Value> = field_values(second, field);
+			let mut ordering: Ordering = compare_sort_values(first_values.first(), second_values.first());
+			if *is_descending {
+				ordering = ordering.reverse();
+			}
+			if ordering != Ordering::Equal {
+				return ordering;
+			}
+		}
+		Ordering::Equal
+	});
+}
+
+//	Words of the text values of a query, as `query` of `match` or `value` of `term`
+fn query_words(_query: &Value) -> Vec<String> {
+	let mut words: Vec<String> = Vec::new();
+	match _query {
+		Value::Object(map) => {
+			for (key, value) in map {
+				let text: Option<&Value> = match key.as_str() {
+					// short form, as `{ "match": { "name": "..." } }`
+					"match" | "match_phrase" => leaf_query(value, "query").map(|(_, text, _)| text),
+					"term" | "prefix" => leaf_query(value, "value").map(|(_, text, _)| text),
+					"query" | "value" => Some(value),
+					_ => None
+				};
+				match text {
+					Some(Value::String(text)) => words.extend(tokens(text)),
+					_ => words.extend(query_words(value))
+				}
+			}
+		},
+		Value::Array(items) => {
+			for item in items {
+				words.extend(query_words(item));
+			}
+		},
+		_ => {}
+	}
+	words
+}
+
+//	Highlight the words of a text that are (or start with) a query word
+fn highlight_text(_text: &str, _words: &[String], _pre_tag: &str, _post_tag: &str) -> Option<String> {
+	let mut fragment: String = String::new();
+	let mut word: String = String::new();
+	let mut is_highlighted: bool = false;
+	let mut push_word = |fragment: &mut String, word: &mut String| {
+		if word.is_empty() {
+			return;
+		}
+		let token: String = word.to_lowercase();
+		if _words.iter().any(|expected| token.starts_with(expected.as_str())) {
+			fragment.push_str(_pre_tag);
+			fragment.push_str(word);
+			fragment.push_str(_post_tag);
+			is_highlighted = true;
+		} else {
+			fragment.push_str(word);
+		}
+		word.clear();
+	};
+	for character in _text.chars() {
+		if character.is_alphanumeric() {
+			word.push(character);
+		} else {
+			push_word(&mut fragment, &mut word);
+			fragment.push(character);
+		}
+	}
+	push_word(&mut fragment, &mut word);
+	if is_highlighted {
+		Some(fragment)
+	} else {
+		None
+	}
+}
+
+//	Fragments of the fields of `_parameters` (as OpenSearch `highlight`), a
+//	fragment is the whole value of field
+fn highlight_fields(_source: &Value, _parameters: &Value, _words: &[String]) -> HashMap<String, Vec<String>> {
+	let mut highlight: HashMap<String, Vec<String>> = HashMap::new();
+	let fields = match _parameters.get("fields").and_then(|value| value.as_object()) {
+		Some(value) => value,
+		None => return highlight
+	};
+	let first_tag = |name: &str, default_value: &'static str| -> String {
+		_parameters.get(name)
+			.and_then(|tags| tags.get(0))
+			.and_then(|tag| tag.as_str())
+			.unwrap_or(default_value)
+			.to_owned()
+	};
+	let pre_tag: String = first_tag("pre_tags", "<em>");
+	let post_tag: String = first_tag("post_tags", "</em>");
+	for field in fields.keys() {
+		let fragments: Vec<String> = field_values(_source, field).iter()
+			.filter_map(|value| value.as_str())
+			.filter_map(|text| highlight_text(text, _words, &pre_tag, &post_tag))
+			.collect()
+		;
+		if !fragments.is_empty() {
+			highlight.insert(field.to_owned(), fragments);
+		}
+	}
+	highlight
+}
+
+#[cfg(test)]
+mod tests {
+	use serde_json::json;
+
+	use super::*;
+	use crate::controller::search::search_body;
+	use crate::models::menu::allowed_menu;
+	use crate::models::window::window_from_id;
+
+	fn window_source() -> Value {
+		json!({
+			"id": "window-1",
+			"uuid": "a1b2",
+			"name": "Sales Order",
+			"description": "Customer orders",
+			"help": "Invoice a customer",
+			"is_active": true,
+			"sequence": 20,
+			"tabs": [
+				{ "name": "Order", "sequence": 10 },
+				{ "name": "Order Line", "sequence": 20 }
+			]
+		})
+	}
+
+	fn chars(_text: &str) -> Vec<char> {
+		_text.chars().collect()
+	}
+
+	//	Store of models tests, with a window, a role and its menu
+	fn dictionary_store() -> MemoryStore {
+		let store: MemoryStore = MemoryStore::new();
+		store.put("window_en_us", "window-1".to_owned(), window_source(), Some(1));
+		store.put("role_client-1", "role-1".to_owned(), json!({
+			"uuid": "role-1",
+			"name": "Sales",
+			"tree_id": 100,
+			"tree_uuid": "tree-1",
+			"window_access": ["window-1", null]
+		}), None);
+		store.put("menu_item_en_us", "1".to_owned(), json!({
+			"id": "1", "internal_id": 1, "name": "Sales", "is_summary": true
+		}), None);
+		store.put("menu_item_en_us", "2".to_owned(), json!({
+			"id": "2", "internal_id": 2, "name": "Sales Order", "is_summary": false,
+			"action": "W", "action_id": 10, "action_uuid": "window-1"
+		}), None);
+		store.put("menu_item_en_us", "3".to_owned(), json!({
+			"id": "3", "internal_id": 3, "name": "Purchase Order", "is_summary": false,
+			"action": "W", "action_id": 11, "action_uuid": "window-2"
+		}), None);
+		store.put("menu_item_en_us", "4".to_owned(), json!({
+			"id": "4", "internal_id": 4, "name": "Empty", "is_summary": true
+		}), None);
+		store.put("menu_tree", "tree-1".to_owned(), json!({
+			"id": "tree-1",
+			"name": "Main Menu",
+			"children": [
+				{
+					"node_id": 1, "parent_id": 0, "sequence": 10,
+					"children": [
+						{ "node_id": 3, "parent_id": 1, "sequence": 10 },
+						{ "node_id": 2, "parent_id": 1, "sequence": 20 }
+					]
+				},
+				{ "node_id": 4, "parent_id": 0, "sequence": 20, "children": [] }
+			]
+		}), None);
+		store
+	}
+
+	#[test]
+	fn wildcard_match_any_and_single_character() {
+		assert!(wildcard_match(&chars("*voice*"), &chars("invoice")));
+		assert!(wildcard_match(&chars("inv?ice"), &chars("invoice")));
+		assert!(wildcard_match(&chars("*"), &chars("")));
+		assert!(wildcard_match(&chars("sales*order"), &chars("sales purchase order")));
+		assert!(!wildcard_match(&chars("inv?ice"), &chars("invoices")));
+		assert!(wildcard_match(&chars("*50\\%\\*off*"), &chars("sale 50%*off")));
+		assert!(!wildcard_match(&chars("50\\*"), &chars("500")));
+		assert!(!wildcard_match(&chars("*order"), &chars("orders")));
+	}
+
+	#[test]
+	fn edit_distance_of_words() {
+		assert_eq!(edit_distance("invoice", "invoice"), 0);
+		assert_eq!(edit_distance("invoce", "invoice"), 1);
+		assert_eq!(edit_distance("kitten", "sitting"), 3);
+		// a transposition is a single edit
+		assert_eq!(edit_distance("slaes", "sales"), 1);
+		assert_eq!(edit_distance("", "order"), 5);
+	}
+
+	#[test]
+	fn fuzzy_match_with_auto_fuzziness_and_prefix() {
+		let auto: Value = json!("AUTO");
+		assert!(is_fuzzy_match("invoce", "invoice", &auto, 0));
+		// two characters words must be equal
+		assert!(!is_fuzzy_match("at", "it", &auto, 0));
+		assert!(is_fuzzy_match("ordr", "order", &auto, 1));
+		// the prefix must not be changed
+		assert!(!is_fuzzy_match("xrder", "order", &auto, 1));
+		assert!(!is_fuzzy_match("invoce", "invoice", &json!(0), 0));
+		assert!(is_fuzzy_match("salse", "sales", &json!("2"), 0));
+	}
+
+	#[test]
+	fn stale_version() {
+		let record: MemoryRecord = MemoryRecord {
+			source: json!({}),
+			version: Some(10),
+		};
+		assert!(is_stale(Some(&record), Some(9)));
+		assert!(is_stale(Some(&record), Some(10)));
+		assert!(!is_stale(Some(&record), Some(11)));
+		assert!(is_stale_delete(Some(&record), Some(9)));
+		assert!(!is_stale_delete(Some(&record), Some(10)));
+		assert!(!is_stale(Some(&record), None));
+		assert!(!is_stale(None, Some(1)));
+		let without_version: MemoryRecord = MemoryRecord {
+			source: json!({}),
+			version: None,
+		};
+		assert!(!is_stale(Some(&without_version), Some(1)));
+	}
+
+	#[test]
+	fn matches_term_terms_and_exists() {
+		let source: Value = window_source();
+		assert!(matches_query(&source, &json!({ "term": { "uuid.keyword": "a1b2" } })));
+		assert!(matches_query(&source, &json!({ "term": { "is_active": { "value": true } } })));
+		assert!(!matches_query(&source, &json!({ "term": { "uuid": "other" } })));
+		assert!(matches_query(&source, &json!({ "terms": { "id": ["window-2", "window-1"] } })));
+		assert!(!matches_query(&source, &json!({ "terms": { "id": ["window-2"] } })));
+		assert!(matches_query(&source, &json!({ "term": { "tabs.name.keyword": "Order Line" } })));
+		assert!(matches_query(&source, &json!({ "exists": { "field": "help" } })));
+		assert!(!matches_query(&source, &json!({ "exists": { "field": "process" } })));
+		assert!(matches_query(&source, &json!({ "ids": { "values": ["window-1"] } })));
+	}
+
+	#[test]
+	fn matches_bool_clauses() {
+		let source: Value = window_source();
+		let must_and_not: Value = json!({
+			"bool": {
+				"must": [{ "term": { "id": "window-1" } }],
+				"must_not": [{ "term": { "is_active": false } }]
+			}
+		});
+		assert!(matches_query(&source, &must_and_not));
+		let must_not: Value = json!({ "bool": { "must_not": { "term": { "id": "window-1" } } } });
+		assert!(!matches_query(&source, &must_not));
+		// without must, a should clause is required
+		let should: Value = json!({ "bool": { "should": [{ "term": { "id": "other" } }, { "term": { "name.keyword": "Sales Order" } }] } });
+		assert!(matches_query(&source, &should));
+		let should_none: Value = json!({ "bool": { "should": [{ "term": { "id": "other" } }] } });
+		assert!(!matches_query(&source, &should_none));
+		// with filter the should clauses are optional
+		let optional_should: Value = json!({ "bool": { "filter": [{ "term": { "id": "window-1" } }], "should": [{ "term": { "id": "other" } }] } });
+		assert!(matches_query(&source, &optional_should));
+		let minimum_should: Value = json!({
+			"bool": {
+				"should": [{ "term": { "id": "window-1" } }, { "term": { "uuid": "other" } }],
+				"minimum_should_match": 2
+			}
+		});
+		assert!(!matches_query(&source, &minimum_should));
+	}
+
+	#[test]
+	fn matches_range_wildcard_and_prefix() {
+		let source: Value = window_source();
+		assert!(matches_query(&source, &json!({ "range": { "sequence": { "gte": 20, "lt": 30 } } })));
+		assert!(!matches_query(&source, &json!({ "range": { "sequence": { "gt": 20 } } })));
+		assert!(matches_query(&source, &json!({ "wildcard": { "name.keyword": { "value": "*ORDER*" } } })));
+		assert!(matches_query(&source, &json!({ "wildcard": { "help": "*voice" } })));
+		assert!(!matches_query(&source, &json!({ "wildcard": { "name": "purchase*" } })));
+		assert!(matches_query(&source, &json!({ "prefix": { "name": "sal" } })));
+	}
+
+	#[test]
+	fn matches_text_queries() {
+		let source: Value = window_source();
+		assert!(matches_query(&source, &json!({ "match": { "name": "order purchase" } })));
+		assert!(!matches_query(&source, &json!({ "match": { "name": { "query": "order purchase", "operator": "and" } } })));
+		assert!(matches_query(&source, &json!({ "match_phrase": { "name": "sales order" } })));
+		assert!(matches_query(&source, &json!({ "match": { "name": { "query": "slaes", "fuzziness": "AUTO" } } })));
+		assert!(!matches_query(&source, &json!({ "match": { "name": "slaes" } })));
+		let as_you_type: Value = json!({
+			"multi_match": {
+				"query": "sales ord",
+				"type": "bool_prefix",
+				"fields": ["name.as_you_type", "name.as_you_type._2gram"]
+			}
+		});
+		assert!(matches_query(&source, &as_you_type));
+		let incomplete_first_word: Value = json!({
+			"multi_match": { "query": "sal order", "type": "bool_prefix", "fields": ["name.as_you_type"] }
+		});
+		assert!(!matches_query(&source, &incomplete_first_word));
+		let multi_match: Value = json!({ "multi_match": { "query": "customer", "fields": ["name^3", "description"] } });
+		assert!(matches_query(&source, &multi_match));
+		assert!(matches_query(&source, &json!({ "query_string": { "query": "*voic*", "fields": ["help"] } })));
+	}
+
+	#[test]
+	fn matches_search_body_with_part_of_name() {
+		let source: Value = window_source();
+		let fields: [&str; 3] = ["name^3", "description", "help"];
+		assert!(matches_query(&source, &search_body("les ord", &fields)["query"]));
+		assert!(matches_query(&source, &search_body("customer", &fields)["query"]));
+		assert!(!matches_query(&source, &search_body("purchase", &fields)["query"]));
+		// a `*` of search value is not a wildcard
+		assert!(!matches_query(&source, &search_body("les*ord", &fields)["query"]));
+		assert!(matches_query(&json!({ "name": "Invoice" }), &search_body("voice", &fields)["query"]));
+	}
+
+	#[test]
+	fn matches_all_and_unsupported_queries() {
+		let source: Value = window_source();
+		assert!(matches_query(&source, &Value::Null));
+		assert!(matches_query(&source, &json!({ "match_all": {} })));
+		assert!(!matches_query(&source, &json!({ "match_none": {} })));
+		assert!(!matches_query(&source, &json!({ "geo_distance": { "distance": "1km" } })));
+	}
+
+	#[test]
+	fn search_hits_sorted_and_paged() {
+		let store: MemoryStore = MemoryStore::new();
+		for (id, name, sequence) in [("1", "Invoice Vendor", 30), ("2", "Invoice Customer", 10), ("3", "Payment", 20)] {
+			store.put("window_en_us", id.to_owned(), json!({ "id": id, "name": name, "sequence": sequence }), None);
+		}
+		let body: Value = json!({
+			"query": { "match": { "name": "invoice" } },
+			"sort": [{ "sequence": { "order": "asc" } }],
+			"highlight": { "fields": { "name": {} } }
+		});
+		let result: SearchHits = store.search_hits("window_en_us", &body, 0, 1).expect("search");
+		assert_eq!(result.total, 2);
+		assert_eq!(result.hits.len(), 1);
+		assert_eq!(result.hits[0].source["id"], json!("2"));
+		assert_eq!(result.hits[0].highlight.get("name"), Some(&vec!["<em>Invoice</em> Customer".to_owned()]));
+		assert!(matches!(store.search_hits("process_en_us", &body, 0, 1), Err(DictionaryError::IndexMissing(_))));
+	}
+
+	#[tokio::test]
+	async fn window_from_id_on_memory_store() {
+		let store: &dyn DictionaryStore = &dictionary_store();
+		let language: String = "en_US".to_owned();
+		let window = window_from_id(store, Some("window-1".to_owned()), Some(&language), None).await.expect("window");
+		assert_eq!(window.name.as_deref(), Some("Sales Order"));
+		let tabs: Vec<String> = window.tabs.unwrap_or_default().into_iter().filter_map(|tab| tab.name).collect();
+		assert_eq!(tabs, vec!["Order".to_owned(), "Order Line".to_owned()]);
+
+		assert!(matches!(window_from_id(store, Some("window-9".to_owned()), Some(&language), None).await, Err(DictionaryError::NotFound(_))));
+		assert!(matches!(window_from_id(store, None, Some(&language), None).await, Err(DictionaryError::BadRequest(_))));
+		assert!(matches!(window_from_id(store, Some("window-1".to_owned()), None, None).await, Err(DictionaryError::BadRequest(_))));
+		let spanish: String = "es_MX".to_owned();
+		assert!(matches!(window_from_id(store, Some("window-1".to_owned()), Some(&spanish), None).await, Err(DictionaryError::IndexMissing(_))));
+	}
+
+	#[tokio::test]
+	async fn allowed_menu_on_memory_store() {
+		let store: &dyn DictionaryStore = &dictionary_store();
+		let language: String = "en_US".to_owned();
+		let client: String = "client-1".to_owned();
+		let role: String = "role-1".to_owned();
+		let response = allowed_menu(store, Some(&language), Some(&client), Some(&role), None).await.expect("menu");
+		let menus = response.menus.expect("menus");
+		// the summary without allowed actions is not returned
+		assert_eq!(menus.len(), 1);
+		assert_eq!(menus[0].name.as_deref(), Some("Sales"));
+		let children: Vec<String> = menus[0].children.to_owned().unwrap_or_default().into_iter().filter_map(|menu| menu.name).collect();
+		assert_eq!(children, vec!["Sales Order".to_owned()]);
+
+		let other_role: String = "role-9".to_owned();
+		assert!(matches!(allowed_menu(store, Some(&language), Some(&client), Some(&other_role), None).await, Err(DictionaryError::NotFound(_))));
+		assert!(matches!(allowed_menu(store, Some(&language), None, Some(&role), None).await, Err(DictionaryError::BadRequest(_))));
+	}
+}
@@ -1,2 +1,11 @@
+pub mod analysis;
+pub mod file;
+pub mod headers;
 pub mod kafka;
-pub mod opensearch;
\ No newline at end of file
+pub mod mapping;
+pub mod memory;
+pub mod opensearch;
+pub mod resilience;
+pub mod search;
+pub mod store;
+pub mod topic;
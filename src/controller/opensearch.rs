@@ -1,14 +1,33 @@
-use std::env;
+use std::{env, fs};
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
 
+use async_trait::async_trait;
 use opensearch::http::response::Response;
-use opensearch::{OpenSearch, IndexParts, DeleteParts, SearchParts, GetParts};
+use futures::{stream, Stream, StreamExt};
+use opensearch::{OpenSearch, BulkParts, ClearScrollParts, IndexParts, DeleteParts, ScrollParts, SearchParts, GetParts, MgetParts, UpdateParts};
+use opensearch::http::request::JsonBody;
+use opensearch::params::{Refresh, VersionType};
 use opensearch::http::Url;
-use opensearch::http::transport::{SingleNodeConnectionPool, Transport, TransportBuilder};
-use opensearch::indices::{IndicesGetParts, IndicesCreateParts, IndicesDeleteParts};
+use base64::Engine;
+use base64::prelude::BASE64_STANDARD;
+use opensearch::auth::{ClientCertificate, Credentials};
+use opensearch::cert::{Certificate, CertificateValidation};
+use opensearch::http::headers::{HeaderValue, AUTHORIZATION};
+use opensearch::http::transport::{Connection, ConnectionPool, Transport, TransportBuilder};
+use opensearch::nodes::NodesInfoParts;
+use opensearch::indices::{IndicesGetParts, IndicesCreateParts, IndicesDeleteParts, IndicesGetAliasParts, IndicesGetMappingParts, IndicesPutIndexTemplateParts, IndicesPutMappingParts, IndicesRefreshParts};
 use salvo::http::StatusCode;
-use serde_json::Value;
 
-pub trait IndexDocument: Sync {
+use crate::controller::resilience::{get_circuit_breaker, send_once, send_with_retry, CircuitBreaker};
+use crate::controller::search::{SearchHit, SearchHits};
+use crate::controller::store::{DictionaryStore, StoreHealth};
+use crate::error::DictionaryError;
+use serde_json::{json, Value};
+
+pub trait IndexDocument: Send + Sync {
     //  A index definition for mapping
     fn mapping(self: &Self) -> serde_json::Value;
     //  Get data for insert
@@ -19,49 +38,360 @@ pub trait IndexDocument: Sync {
     fn id(self: &Self) -> String;
     //  Make a search based on _search_value
     fn find(self: &Self, _search_value: String) -> serde_json::Value;
+    //  Version of record from ADempiere event (as updated timestamp in milliseconds),
+    //  used as external version: an event that is not newer than the stored
+    //  document is rejected, except a delete of the same version
+    fn version(self: &Self) -> Option<i64> {
+        None
+    }
 }
 
-pub fn create_opensearch_client() -> Result<OpenSearch, String> {
-	let opensearch_url: String =  match env::var("OPENSEARCH_URL") {
-        Ok(value) => value.clone(),
-        Err(_) => {
-            log::warn!("Variable `OPENSEARCH_URL` Not found from enviroment, loaded with `default` value");
-            "http://localhost:9200".to_owned()
-        }.to_owned(),
-    };
-	let url: Url = match Url::parse(&opensearch_url) {
-        Ok(value) => value,
-        Err(error) => {
-            return Err(error.to_string());
-        },
-    };
-	let conn_pool: SingleNodeConnectionPool = SingleNodeConnectionPool::new(url);
-	let transport: Transport = match TransportBuilder::new(conn_pool)
-        .disable_proxy()
-        // .auth(Credentials::Basic("admin".to_owned(), "admin".to_owned()))
-        .build() {
-            Ok(value) => value,
-            Err(error) => {
-                return Err(error.to_string());
-            },
-        };
-    Ok(OpenSearch::new(transport))
-}
-
-pub async fn exists_index(_index_name: String) -> Result<bool, String> {
-	let client: OpenSearch = create_opensearch_client()?;
+fn get_env_value(_key: &str, _default_value: &str) -> String {
+	match env::var(_key) {
+		Ok(value) => value,
+		Err(_) => {
+			log::warn!("Variable `{}` Not found from enviroment, loaded with `default` value {:?}", _key, _default_value);
+			_default_value.to_owned()
+		}
+	}
+}
 
-    //  Get data
-	let _response: Result<opensearch::http::response::Response, opensearch::Error> = client.indices()
-        .get(IndicesGetParts::Index(&[&_index_name]))
+pub(crate) fn get_env_number(_key: &str, _default_value: u64) -> u64 {
+	let value: String = get_env_value(_key, &_default_value.to_string());
+	match value.trim().parse::<u64>() {
+		Ok(number) => number,
+		Err(error) => {
+			log::warn!("Variable `{}` with invalid value {:?} ({}), loaded with `default` value {:?}", _key, value, error, _default_value);
+			_default_value
+		}
+	}
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ConnectionPoolType {
+	//	Use only the configured node list
+	Static,
+	//	Refresh the node list from cluster (`_nodes/http`) using the configured nodes as seeds
+	Sniffing,
+}
+
+#[derive(Debug, Clone)]
+pub struct OpenSearchConfig {
+	pub urls: Vec<String>,
+	pub connection_pool: ConnectionPoolType,
+	//	Interval in seconds to refresh the node list with a sniffing pool
+	pub sniff_interval: u64,
+	//	Number of clients (each one with its own connection pool) shared by the service
+	pub pool_size: usize,
+	//	Request timeout in seconds, 0 to disable it
+	pub timeout: u64,
+	//	Basic authentication
+	pub username: Option<String>,
+	pub password: Option<String>,
+	//	API Key authentication
+	pub api_key_id: Option<String>,
+	pub api_key: Option<String>,
+	//	TLS
+	pub ca_certificate: Option<String>,
+	pub verify_certificate: bool,
+	pub client_certificate: Option<String>,
+	pub client_certificate_password: Option<String>,
+}
+
+impl Default for OpenSearchConfig {
+	fn default() -> Self {
+		Self {
+			urls: vec!["http://localhost:9200".to_owned()],
+			connection_pool: ConnectionPoolType::Static,
+			sniff_interval: 300,
+			pool_size: 1,
+			timeout: 30,
+			username: None,
+			password: None,
+			api_key_id: None,
+			api_key: None,
+			ca_certificate: None,
+			verify_certificate: true,
+			client_certificate: None,
+			client_certificate_password: None,
+		}
+	}
+}
+
+fn get_env_optional(_key: &str) -> Option<String> {
+	match env::var(_key) {
+		Ok(value) if !value.trim().is_empty() => Some(value.trim().to_owned()),
+		_ => None
+	}
+}
+
+impl OpenSearchConfig {
+	pub fn from_env() -> Self {
+		let default_config: OpenSearchConfig = OpenSearchConfig::default();
+		// many nodes can be defined using space or comma between urls
+		let urls: Vec<String> = get_env_value("OPENSEARCH_URL", &default_config.urls.join(" "))
+			.split(|character: char| character.is_whitespace() || character == ',')
+			.filter(|url| !url.trim().is_empty())
+			.map(|url| url.trim().to_owned())
+			.collect()
+		;
+		let connection_pool: ConnectionPoolType = match get_env_value("OPENSEARCH_CONNECTION_POOL", "static").trim().to_lowercase().as_str() {
+			"sniffing" => ConnectionPoolType::Sniffing,
+			"static" => ConnectionPoolType::Static,
+			value => {
+				log::warn!("Variable `OPENSEARCH_CONNECTION_POOL` with invalid value {:?}, loaded as `static`", value);
+				ConnectionPoolType::Static
+			}
+		};
+		let pool_size: u64 = get_env_number("OPENSEARCH_POOL_SIZE", default_config.pool_size as u64);
+		Self {
+			urls: if urls.is_empty() { default_config.urls } else { urls },
+			connection_pool,
+			sniff_interval: get_env_number("OPENSEARCH_SNIFF_INTERVAL", default_config.sniff_interval),
+			pool_size: pool_size.max(1) as usize,
+			timeout: get_env_number("OPENSEARCH_TIMEOUT", default_config.timeout),
+			username: get_env_optional("OPENSEARCH_USERNAME"),
+			password: get_env_optional("OPENSEARCH_PASSWORD"),
+			api_key_id: get_env_optional("OPENSEARCH_API_KEY_ID"),
+			api_key: get_env_optional("OPENSEARCH_API_KEY"),
+			ca_certificate: get_env_optional("OPENSEARCH_CA_CERTIFICATE"),
+			verify_certificate: !get_env_value("OPENSEARCH_VERIFY_CERTIFICATE", "Y").trim().eq("N"),
+			client_certificate: get_env_optional("OPENSEARCH_CLIENT_CERTIFICATE"),
+			client_certificate_password: get_env_optional("OPENSEARCH_CLIENT_CERTIFICATE_PASSWORD"),
+		}
+	}
+}
+
+//	Round robin connection pool over many OpenSearch nodes, the node list
+//	can be refreshed from cluster when sniffing is enabled
+#[derive(Debug, Clone)]
+pub struct MultiNodeConnectionPool {
+	urls: Arc<RwLock<Vec<Url>>>,
+	next_connection: Arc<AtomicUsize>,
+}
+
+impl MultiNodeConnectionPool {
+	pub fn new(_urls: Vec<Url>) -> Self {
+		Self {
+			urls: Arc::new(RwLock::new(_urls)),
+			next_connection: Arc::new(AtomicUsize::new(0)),
+		}
+	}
+
+	pub fn urls(&self) -> Vec<Url> {
+		match self.urls.read() {
+			Ok(urls) => urls.clone(),
+			Err(error) => error.into_inner().clone()
+		}
+	}
+
+	pub fn set_urls(&self, _urls: Vec<Url>) {
+		if _urls.is_empty() {
+			log::warn!("Empty OpenSearch node list, keeping current nodes");
+			return;
+		}
+		match self.urls.write() {
+			Ok(mut urls) => *urls = _urls,
+			Err(error) => *error.into_inner() = _urls
+		}
+	}
+}
+
+impl ConnectionPool for MultiNodeConnectionPool {
+	fn next(&self) -> Connection {
+		let urls: Vec<Url> = self.urls();
+		let index: usize = self.next_connection.fetch_add(1, Ordering::Relaxed) % urls.len();
+		Connection::new(urls[index].clone())
+	}
+}
+
+pub struct OpenSearchPool {
+	clients: Vec<OpenSearch>,
+	next_client: AtomicUsize,
+}
+
+impl OpenSearchPool {
+	pub fn new(_config: &OpenSearchConfig) -> Result<Self, String> {
+		let mut urls: Vec<Url> = Vec::new();
+		for url in &_config.urls {
+			match Url::parse(url) {
+				Ok(value) => urls.push(value),
+				Err(error) => {
+					return Err(format!("Invalid OpenSearch URL {:?}: {}", url, error));
+				},
+			};
+		}
+		if urls.is_empty() {
+			return Err("OpenSearch URL is Mandatory".to_owned());
+		}
+		let conn_pool: MultiNodeConnectionPool = MultiNodeConnectionPool::new(urls);
+
+		let mut clients: Vec<OpenSearch> = Vec::new();
+		for _ in 0.._config.pool_size.max(1) {
+			clients.push(create_opensearch_client(_config, conn_pool.clone())?);
+		}
+		log::info!("OpenSearch client pool created with {} client(s) to {:?}", clients.len(), _config.urls);
+
+		if _config.connection_pool == ConnectionPoolType::Sniffing {
+			match tokio::runtime::Handle::try_current() {
+				Ok(runtime) => {
+					runtime.spawn(
+						sniff_nodes(clients[0].clone(), conn_pool, _config.sniff_interval)
+					);
+				},
+				Err(error) => log::warn!("OpenSearch node sniffing is disabled, without async runtime: {}", error)
+			}
+		}
+		Ok(Self {
+			clients,
+			next_client: AtomicUsize::new(0),
+		})
+	}
+
+	//	Round robin between pooled clients
+	pub fn client(&self) -> &OpenSearch {
+		let index: usize = self.next_client.fetch_add(1, Ordering::Relaxed) % self.clients.len();
+		&self.clients[index]
+	}
+}
+
+async fn sniff_nodes(_client: OpenSearch, _conn_pool: MultiNodeConnectionPool, _interval: u64) {
+	let seed_urls: Vec<Url> = _conn_pool.urls();
+	let scheme: String = seed_urls[0].scheme().to_owned();
+	loop {
+		match get_node_urls(&_client, &scheme).await {
+			Ok(urls) => {
+				log::debug!("OpenSearch nodes found: {:?}", urls.iter().map(|url| url.to_string()).collect::<Vec<String>>());
+				_conn_pool.set_urls(urls);
+			},
+			Err(error) => {
+				log::warn!("OpenSearch node sniffing error, using seed nodes: {}", error);
+				_conn_pool.set_urls(seed_urls.clone());
+			}
+		};
+		tokio::time::sleep(Duration::from_secs(_interval.max(1))).await;
+	}
+}
+
+async fn get_node_urls(_client: &OpenSearch, _scheme: &str) -> Result<Vec<Url>, String> {
+	let response: Response = match _client.nodes()
+		.info(NodesInfoParts::Metric(&["http"]))
 		.send()
-		.await
+		.await {
+			Ok(value) => value,
+			Err(error) => return Err(error.to_string())
+		};
+	if !response.status_code().is_success() {
+		return Err(format!("Error getting nodes info {:?}", response.text().await));
+	}
+	let response_body: Value = match response.json::<Value>().await {
+		Ok(value) => value,
+		Err(error) => return Err(error.to_string())
+	};
+
+	let mut urls: Vec<Url> = Vec::new();
+	if let Some(nodes) = response_body["nodes"].as_object() {
+		for node in nodes.values() {
+			// publish address can be `hostname/ip:port` or `ip:port`
+			let publish_address: &str = match node["http"]["publish_address"].as_str() {
+				Some(value) => value.rsplit('/').next().unwrap_or(value),
+				None => continue
+			};
+			match Url::parse(&format!("{}://{}", _scheme, publish_address)) {
+				Ok(url) => urls.push(url),
+				Err(error) => log::warn!("Invalid node address {:?}: {}", publish_address, error)
+			}
+		}
+	}
+	Ok(urls)
+}
+
+fn read_file(_path: &str) -> Result<Vec<u8>, String> {
+	fs::read(_path).map_err(|error| format!("Error reading file {:?}: {}", _path, error))
+}
+
+pub fn create_opensearch_client(_config: &OpenSearchConfig, _conn_pool: MultiNodeConnectionPool) -> Result<OpenSearch, String> {
+	let mut builder: TransportBuilder = TransportBuilder::new(_conn_pool)
+		.disable_proxy()
 	;
+
+	//	Authentication
+	let basic_credentials: Option<Credentials> = match (&_config.username, &_config.password) {
+		(Some(username), password) => Some(
+			Credentials::Basic(username.to_owned(), password.to_owned().unwrap_or_default())
+		),
+		_ => None
+	};
+	let api_key_credentials: Option<Credentials> = match (&_config.api_key_id, &_config.api_key) {
+		(Some(id), Some(key)) => Some(
+			Credentials::ApiKey(id.to_owned(), key.to_owned())
+		),
+		_ => None
+	};
+	let credentials: Option<Credentials> = api_key_credentials.or(basic_credentials);
+
+	if let Some(certificate_path) = &_config.client_certificate {
+		//	Client certificate as PKCS #12 archive, it takes the place of credentials
+		//	on transport so the basic or api key authentication is sent as header
+		let certificate: Vec<u8> = read_file(certificate_path)?;
+		builder = builder.auth(
+			Credentials::Certificate(
+				ClientCertificate::Pkcs12(certificate, _config.client_certificate_password.to_owned())
+			)
+		);
+		if let Some(value) = credentials {
+			builder = builder.header(AUTHORIZATION, authorization_header(value)?);
+		}
+	} else if let Some(value) = credentials {
+		builder = builder.auth(value);
+	}
+
+	//	Server certificate validation
+	if !_config.verify_certificate {
+		log::warn!("OpenSearch certificate validation is disabled");
+		builder = builder.cert_validation(CertificateValidation::None);
+	} else if let Some(certificate_path) = &_config.ca_certificate {
+		let certificate: Certificate = match Certificate::from_pem(&read_file(certificate_path)?) {
+			Ok(value) => value,
+			Err(error) => return Err(format!("Invalid CA certificate {:?}: {}", certificate_path, error))
+		};
+		builder = builder.cert_validation(CertificateValidation::Full(certificate));
+	}
+
+	if _config.timeout > 0 {
+		builder = builder.timeout(Duration::from_secs(_config.timeout));
+	}
+	let transport: Transport = match builder.build() {
+		Ok(value) => value,
+		Err(error) => {
+			return Err(error.to_string());
+		},
+	};
+	Ok(OpenSearch::new(transport))
+}
+
+fn authorization_header(_credentials: Credentials) -> Result<HeaderValue, String> {
+	let value: String = match _credentials {
+		Credentials::Basic(username, password) => format!("Basic {}", BASE64_STANDARD.encode(format!("{}:{}", username, password))),
+		Credentials::ApiKey(id, key) => format!("ApiKey {}", BASE64_STANDARD.encode(format!("{}:{}", id, key))),
+		Credentials::Bearer(token) => format!("Bearer {}", token),
+		_ => return Err("Unsupported credentials for authorization header".to_owned())
+	};
+	HeaderValue::from_str(&value).map_err(|error| error.to_string())
+}
+
+pub async fn exists_index(_client: &OpenSearch, _index_name: String) -> Result<bool, DictionaryError> {
+    //  Get data
+	let _response: Result<Response, DictionaryError> = send_with_retry(|| async {
+		_client.indices()
+			.get(IndicesGetParts::Index(&[&_index_name]))
+			.send().await
+	}).await;
 	let response: Response = match _response {
         Ok(value) => value,
         Err(error) => {
             log::error!("{:?}", error);
-            return Err(error.to_string());
+            return Err(error);
         }
     };
 
@@ -72,113 +402,537 @@ pub async fn exists_index(_index_name: String) -> Result<bool, String> {
 		// Not exists
 		Ok(false)
     } else {
-        Err(format!("Index {:?} Not Found", _index_name))
+        Err(DictionaryError::BackendUnavailable(format!("Index {:?} Not Found", _index_name)))
     }
 }
 
-pub async fn create_index_definition(_index: &dyn IndexDocument) -> Result<bool, String> {
+//	Physical index name for an alias version, as `window_es_mx_v2`
+pub fn versioned_index_name(_alias: &str, _version: u64) -> String {
+	format!("{}_v{}", _alias, _version)
+}
+
+//	Version of a physical index behind an alias, 0 for indices without version
+fn index_version(_alias: &str, _physical_index_name: &str) -> u64 {
+	match _physical_index_name.strip_prefix(&format!("{}_v", _alias)) {
+		Some(version) => version.parse::<u64>().unwrap_or(0),
+		None => 0
+	}
+}
+
+//	Get the physical index behind an alias, indices created before the alias
+//	support (without version) are returned as they are
+pub async fn get_alias_index(_client: &OpenSearch, _alias: String) -> Result<Option<String>, DictionaryError> {
+	let _response: Result<Response, DictionaryError> = send_with_retry(|| async {
+		_client.indices()
+			.get_alias(IndicesGetAliasParts::Name(&[&_alias]))
+			.send().await
+	}).await;
+	let response: Response = match _response {
+		Ok(value) => value,
+		Err(error) => {
+			log::error!("{:?}", error);
+			return Err(error);
+		}
+	};
+	let status: StatusCode = response.status_code();
+	if status.as_u16() == 404 {
+		// Not an alias, can be an index without version
+		if exists_index(_client, _alias.to_owned()).await? {
+			return Ok(Some(_alias));
+		}
+		return Ok(None);
+	}
+	if !status.is_success() {
+		return Err(DictionaryError::BackendUnavailable(format!("Error getting alias {:?} ({:?})", _alias, response.text().await)));
+	}
+	let response_body: Value = match response.json::<Value>().await {
+		Ok(value) => value,
+		Err(error) => {
+			log::error!("{:?}", error);
+			return Err(error.into());
+		},
+	};
+	// the last version when the alias is (wrongly) shared by many indices
+	let physical_index_name: Option<String> = response_body.as_object()
+		.and_then(|indices| {
+			indices.keys()
+				.max_by_key(|index_name| index_version(&_alias, index_name))
+				.cloned()
+		})
+	;
+	Ok(physical_index_name)
+}
+
+async fn create_physical_index(_client: &OpenSearch, _index_name: &str, _body: Value) -> Result<bool, DictionaryError> {
+	let _response: Result<Response, DictionaryError> = send_with_retry(|| async {
+		_client
+			.indices()
+			.create(IndicesCreateParts::Index(_index_name))
+			.body(_body.clone())
+			.send().await
+	}).await;
+	match _response {
+		Ok(value) => {
+			if value.status_code().is_success() {
+				log::info!("Index created: {:?}", _index_name);
+				Ok(true)
+			} else {
+				Err(
+					DictionaryError::BackendUnavailable(format!("Error creating index {:?} ({:?})", _index_name, value.text().await))
+				)
+			}
+		}
+		Err(error) => {
+			log::error!("{:?}", error);
+			Err(error)
+		}
+	}
+}
+
+async fn delete_physical_index(_client: &OpenSearch, _index_name: &str) -> Result<bool, DictionaryError> {
+	let _response: Result<Response, DictionaryError> = send_with_retry(|| async {
+		_client
+			.indices()
+			.delete(IndicesDeleteParts::Index(&[_index_name]))
+			.send().await
+	}).await;
+	match _response {
+		Ok(value) => {
+			if value.status_code().is_success() || value.status_code().as_u16() == 404 {
+				log::info!("Index deleted: {:?}", _index_name);
+				Ok(true)
+			} else {
+				Err(DictionaryError::BackendUnavailable(format!("Error deleting index {:?}({:?})", _index_name, value.status_code())))
+			}
+		}
+		Err(error) => {
+			log::error!("{:?}", error);
+			Err(error)
+		}
+	}
+}
+
+//	Create the first version of physical index with the alias of `_index.index_name()`
+pub async fn create_index_definition(_client: &OpenSearch, _index: &dyn IndexDocument) -> Result<bool, DictionaryError> {
 	let index_name: String = _index.index_name();
-	if exists_index(index_name.clone()).await? {
+	if exists_index(_client, index_name.clone()).await? {
 		log::debug!("Index {:?} already exist, skipping creation.", index_name);
 		return Ok(true);
 	}
 
-	let client: OpenSearch = create_opensearch_client()?;
+	let physical_index_name: String = versioned_index_name(&index_name, 1);
+	let mut body: Value = _index.mapping();
+	body["aliases"] = json!({
+		index_name.to_owned(): {}
+	});
+	match create_physical_index(_client, &physical_index_name, body).await {
+		Ok(_) => Ok(true),
+		Err(error) => {
+			// can be created by another writer at the same time
+			if exists_index(_client, index_name.clone()).await? {
+				log::debug!("Index {:?} already exist, skipping creation.", index_name);
+				return Ok(true);
+			}
+			Err(error)
+		}
+	}
+}
 
-    //  Get data
-	let _response: Result<opensearch::http::response::Response, opensearch::Error> = client.indices()
-        .get(IndicesGetParts::Index(&[&_index.index_name()]))
-		.send()
-		.await
-	;
+pub async fn delete_index_definition(_client: &OpenSearch, _index: &dyn IndexDocument) -> Result<bool, DictionaryError> {
+	let index_name: String = _index.index_name();
+	let physical_index_name: String = match get_alias_index(_client, index_name.clone()).await? {
+		Some(value) => value,
+		None => {
+			log::warn!("Index {:?} does not exist, skipping deletion.", index_name);
+			return Ok(true);
+		}
+	};
+	delete_physical_index(_client, &physical_index_name).await
+}
+
+//	Get stored mappings of indices, as `{ "window_es_mx_v1": { "mappings": { ... } } }`
+pub async fn get_mappings(_client: &OpenSearch, _index_patterns: &[&str]) -> Result<Value, DictionaryError> {
+	let _response: Result<Response, DictionaryError> = send_with_retry(|| async {
+		_client
+			.indices()
+			.get_mapping(IndicesGetMappingParts::Index(_index_patterns))
+			.allow_no_indices(true)
+			.send().await
+	}).await;
 	let response: Response = match _response {
 		Ok(value) => value,
-        Err(error) => {
-            log::error!("{:?}", error);
-            return Err(error.to_string());
-        }
-    };
-    if !response.status_code().is_success() {
-        // Create an index
-		let _response: Result<opensearch::http::response::Response, opensearch::Error> = client
+		Err(error) => {
+			log::error!("{:?}", error);
+			return Err(error);
+		}
+	};
+	if !response.status_code().is_success() {
+		return Err(DictionaryError::BackendUnavailable(format!("Error getting mappings {:?} ({:?})", _index_patterns, response.text().await)));
+	}
+	match response.json::<Value>().await {
+		Ok(value) => Ok(value),
+		Err(error) => {
+			log::error!("{:?}", error);
+			Err(error.into())
+		},
+	}
+}
+
+//	Add fields to mapping of an index, existing fields can not be changed
+pub async fn put_mapping(_client: &OpenSearch, _index_name: &str, _body: Value) -> Result<bool, DictionaryError> {
+	let _response: Result<Response, DictionaryError> = send_with_retry(|| async {
+		_client
 			.indices()
-			.create(IndicesCreateParts::Index(&_index.index_name()))
-			.body(_index.mapping())
-			.send()
-			.await
-		;
-		//
-        match _response {
-            Ok(value) => {
-                if value.status_code().is_success() {
-                    log::info!("Index created: {:?}", _index.index_name());
-                } else {
-					return Err(
-						format!("Error creating index {:?} ({:?})", _index.index_name(), value.text().await)
-					);
-                }
-            }
-            Err(error) => {
-                log::error!("{:?}", error);
-                return Err(error.to_string());
-            }
-        }
-    }
-    Ok(true)
+			.put_mapping(IndicesPutMappingParts::Index(&[_index_name]))
+			.body(_body.clone())
+			.send().await
+	}).await;
+	let response: Response = match _response {
+		Ok(value) => value,
+		Err(error) => {
+			log::error!("{:?}", error);
+			return Err(error);
+		}
+	};
+	if !response.status_code().is_success() {
+		return Err(DictionaryError::BackendUnavailable(format!("Error updating mapping {:?} ({:?})", _index_name, response.text().await)));
+	}
+	log::info!("Mapping updated: {:?}", _index_name);
+	Ok(true)
 }
 
-pub async fn delete_index_definition(_index: &dyn IndexDocument) -> Result<bool, String> {
-	let index_name: String = _index.index_name();
-	if !exists_index(index_name.clone()).await? {
-		log::warn!("Index {:?} does not exist, skipping deletion.", index_name);
-		return Ok(true);
+//	Create or replace an index template, new indices that match its patterns
+//	are created with the template mappings and settings
+pub async fn put_index_template(_client: &OpenSearch, _name: &str, _body: Value) -> Result<bool, DictionaryError> {
+	let _response: Result<Response, DictionaryError> = send_with_retry(|| async {
+		_client
+			.indices()
+			.put_index_template(IndicesPutIndexTemplateParts::Name(_name))
+			.body(_body.clone())
+			.send().await
+	}).await;
+	let response: Response = match _response {
+		Ok(value) => value,
+		Err(error) => {
+			log::error!("{:?}", error);
+			return Err(error);
+		}
+	};
+	if !response.status_code().is_success() {
+		return Err(DictionaryError::BackendUnavailable(format!("Error updating index template {:?} ({:?})", _name, response.text().await)));
 	}
+	log::info!("Index template updated: {:?}", _name);
+	Ok(true)
+}
 
-	let client: OpenSearch = create_opensearch_client()?;
+//	Build a new version of the physical index with the current mapping, copy
+//	the documents from current version and swap the alias atomically, readers
+//	always use the alias so they never see a half-built index. The documents
+//	written during the first copy are copied again after the swap, and the
+//	documents deleted during it are deleted from the new version
+pub async fn reindex(_client: &OpenSearch, _index: &dyn IndexDocument) -> Result<String, DictionaryError> {
+	let alias: String = _index.index_name();
+	let current_index_name: String = match get_alias_index(_client, alias.clone()).await? {
+		Some(value) => value,
+		None => {
+			log::info!("Index {:?} does not exist, creating it.", alias);
+			create_index_definition(_client, _index).await?;
+			return Ok(versioned_index_name(&alias, 1));
+		}
+	};
+	let new_index_name: String = versioned_index_name(&alias, index_version(&alias, &current_index_name) + 1);
+	log::info!("Reindex {:?} from {:?} to {:?}", alias, current_index_name, new_index_name);
 
-    //  Get data
-	let _response: Result<opensearch::http::response::Response, opensearch::Error> = client.indices()
-        .get(IndicesGetParts::Index(&[&_index.index_name()]))
-		.send()
-		.await
-	;
-	let response: Response = match _response {
-        Ok(value) => value,
-        Err(error) => {
-            log::error!("{:?}", error);
-            return Err(error.to_string());
-        }
-    };
-    if response.status_code().is_success() {
-        // Create an index
-		let _response: Result<Response, opensearch::Error> = client
+	// remove a half-built index of a previous failed reindex
+	if exists_index(_client, new_index_name.clone()).await? {
+		delete_physical_index(_client, &new_index_name).await?;
+	}
+	create_physical_index(_client, &new_index_name, _index.mapping()).await?;
+
+	// keep the versions of source documents, so the catch-up copy after
+	// swap only overwrites documents changed during the first copy
+	let copy_body: Value = json!({
+		"conflicts": "proceed",
+		"source": {
+			"index": current_index_name
+		},
+		"dest": {
+			"index": new_index_name,
+			"version_type": "external"
+		}
+	});
+	if let Err(error) = copy_documents(_client, copy_body.clone()).await {
+		delete_physical_index(_client, &new_index_name).await?;
+		return Err(error);
+	}
+	// copied documents, to delete the ones deleted from current version after the copy
+	let copied: HashMap<String, (i64, i64)> = match document_versions(_client, &new_index_name).await {
+		Ok(value) => value,
+		Err(error) => {
+			delete_physical_index(_client, &new_index_name).await?;
+			return Err(error);
+		}
+	};
+	if current_index_name == alias {
+		// index without version is deleted by the swap, without catch-up copy
+		if let Err(error) = delete_removed_documents(_client, &current_index_name, &new_index_name, &copied).await {
+			delete_physical_index(_client, &new_index_name).await?;
+			return Err(error);
+		}
+	}
+
+	let mut actions: Vec<Value> = vec![
+		json!({
+			"add": {
+				"index": new_index_name,
+				"alias": alias
+			}
+		})
+	];
+	if current_index_name == alias {
+		// index without version, it is replaced by the alias
+		actions.push(json!({
+			"remove_index": {
+				"index": current_index_name
+			}
+		}));
+	} else {
+		actions.push(json!({
+			"remove": {
+				"index": current_index_name,
+				"alias": alias
+			}
+		}));
+	}
+	let _response: Result<Response, DictionaryError> = send_with_retry(|| async {
+		_client
 			.indices()
-			.delete(IndicesDeleteParts::Index(&[&_index.index_name()]))
+			.update_aliases()
+			.body(json!({
+				"actions": actions
+			}))
+			.send().await
+	}).await;
+	let response: Response = match _response {
+		Ok(value) => value,
+		Err(error) => {
+			log::error!("{:?}", error);
+			return Err(error);
+		}
+	};
+	if !response.status_code().is_success() {
+		return Err(DictionaryError::BackendUnavailable(format!("Error swapping alias {:?} to {:?} ({:?})", alias, new_index_name, response.text().await)));
+	}
+	log::info!("Alias {:?} swapped to {:?}", alias, new_index_name);
+
+	if current_index_name != alias {
+		// the previous version is kept to copy its last changes again, its
+		// last writes are searchable by the copy after a refresh
+		let catch_up: Result<usize, DictionaryError> = match refresh_index(_client, &current_index_name).await {
+			Ok(_) => match copy_documents(_client, copy_body).await {
+				Ok(_) => delete_removed_documents(_client, &current_index_name, &new_index_name, &copied).await,
+				Err(error) => Err(error)
+			},
+			Err(error) => Err(error)
+		};
+		if let Err(error) = catch_up {
+			log::error!("Catch-up copy from {:?} to {:?} error, index {:?} is not deleted: {}", current_index_name, new_index_name, current_index_name, error);
+			return Err(error);
+		}
+		delete_physical_index(_client, &current_index_name).await?;
+	}
+	// the documents of catch-up copy are searchable when reindex ends
+	refresh_index(_client, &new_index_name).await?;
+	Ok(new_index_name)
+}
+
+//	Documents read by page while a reindex compares indices
+const REINDEX_PAGE_SIZE: i64 = 1000;
+
+//	Sequence number and primary term of every document of a physical index, by id
+async fn document_versions(_client: &OpenSearch, _index_name: &str) -> Result<HashMap<String, (i64, i64)>, DictionaryError> {
+	let body: Value = json!({
+		"_source": false,
+		"seq_no_primary_term": true,
+		"sort": ["_doc"]
+	});
+	let mut versions: HashMap<String, (i64, i64)> = HashMap::new();
+	let mut scroll_id: Option<String> = None;
+	loop {
+		let page: SearchPage = match scroll_hits(_client, _index_name.to_owned(), body.clone(), REINDEX_PAGE_SIZE, scroll_id.to_owned()).await {
+			Ok(value) => value,
+			Err(error) => {
+				if let Some(value) = scroll_id {
+					clear_scroll(_client, value).await;
+				}
+				return Err(error);
+			}
+		};
+		for hit in page.values {
+			if let (Some(id), Some(seq_no), Some(primary_term)) = (hit["_id"].as_str(), hit["_seq_no"].as_i64(), hit["_primary_term"].as_i64()) {
+				versions.insert(id.to_owned(), (seq_no, primary_term));
+			}
+		}
+		match page.scroll_id {
+			Some(value) => scroll_id = Some(value),
+			None => return Ok(versions)
+		}
+	}
+}
+
+//	Delete from the new version the copied documents that are not in the
+//	source index anymore. Each delete requires the sequence number of the
+//	copy, so a document changed after it (as a document created again after
+//	the alias swap) is kept
+async fn delete_removed_documents(_client: &OpenSearch, _source_index_name: &str, _index_name: &str, _copied: &HashMap<String, (i64, i64)>) -> Result<usize, DictionaryError> {
+	refresh_index(_client, _source_index_name).await?;
+	let remaining: HashMap<String, (i64, i64)> = document_versions(_client, _source_index_name).await?;
+	let actions: Vec<Value> = _copied.iter()
+		.filter(|(id, _)| !remaining.contains_key(*id))
+		.map(|(id, (seq_no, primary_term))| json!({
+			"delete": {
+				"_index": _index_name,
+				"_id": id,
+				"if_seq_no": seq_no,
+				"if_primary_term": primary_term
+			}
+		}))
+		.collect()
+	;
+	let mut deleted: usize = 0;
+	for chunk in actions.chunks(REINDEX_PAGE_SIZE as usize) {
+		let _response: Result<Response, DictionaryError> = send_with_retry(|| async {
+			_client
+				.bulk(BulkParts::None)
+				.body(chunk.iter().cloned().map(JsonBody::from).collect::<Vec<JsonBody<Value>>>())
+				.send().await
+		}).await;
+		let response: Response = match _response {
+			Ok(value) => value,
+			Err(error) => {
+				log::error!("{:?}", error);
+				return Err(error);
+			}
+		};
+		if !response.status_code().is_success() {
+			return Err(DictionaryError::BackendUnavailable(format!("Error deleting removed records {:?}", response.text().await)));
+		}
+		let response_body: Value = match response.json::<Value>().await {
+			Ok(value) => value,
+			Err(error) => {
+				log::error!("{:?}", error);
+				return Err(error.into());
+			},
+		};
+		for item in response_body["items"].as_array().cloned().unwrap_or_default() {
+			match item["delete"]["status"].as_u64() {
+				Some(200) => deleted += 1,
+				// changed after the copy, or already deleted by a retried request
+				Some(404) | Some(409) => {},
+				_ => return Err(DictionaryError::BackendUnavailable(format!("Error deleting removed record {:?}", item["delete"])))
+			}
+		}
+	}
+	log::info!("Removed documents deleted from {:?}: {}", _index_name, deleted);
+	Ok(deleted)
+}
+
+async fn copy_documents(_client: &OpenSearch, _body: Value) -> Result<bool, DictionaryError> {
+	// a copy is not retried, it can take a long time
+	let _response: Result<Response, DictionaryError> = send_once(
+		_client
+			.reindex()
+			.body(_body)
+			.refresh(true)
+			// large indices can take more time than the client timeout
+			.request_timeout(Duration::from_secs(3600))
 			.send()
-			.await
-		;
-        //  
-        match _response {
-            Ok(value) => {
-                if value.status_code().is_success() {
-                    log::info!("Index deleted: {:?}", _index.index_name());
-                } else {
-                    return Err(format!("Error deleting index {:?}({:?})", _index.index_name(), value.status_code()));    
-                }
-            }
-            Err(error) => {
-                log::error!("{:?}", error);
-                return Err(error.to_string());
-            }
-        }
-    }
-    Ok(true)
+	).await;
+	let response: Response = match _response {
+		Ok(value) => value,
+		Err(error) => {
+			log::error!("{:?}", error);
+			return Err(error);
+		}
+	};
+	if !response.status_code().is_success() {
+		return Err(DictionaryError::BackendUnavailable(format!("Error copying documents ({:?})", response.text().await)));
+	}
+	let response_body: Value = match response.json::<Value>().await {
+		Ok(value) => value,
+		Err(error) => {
+			log::error!("{:?}", error);
+			return Err(error.into());
+		},
+	};
+	match response_body["failures"].as_array() {
+		Some(failures) if !failures.is_empty() => {
+			Err(DictionaryError::BackendUnavailable(format!("Error copying documents {:?}", failures)))
+		},
+		_ => {
+			log::debug!("Documents copied: {:?}, created: {:?}, updated: {:?}", response_body["total"], response_body["created"], response_body["updated"]);
+			Ok(true)
+		}
+	}
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RefreshPolicy {
+	//	Changes are searchable after the next periodic refresh of index
+	None,
+	//	The write waits for the next periodic refresh
+	WaitFor,
+	//	The changed shards are refreshed after the write, it is expensive on
+	//	heavy ingest
+	Immediate,
+}
+
+impl RefreshPolicy {
+	pub fn from_env() -> Self {
+		let refresh_policy: String = get_env_value("OPENSEARCH_REFRESH_POLICY", "none");
+		match refresh_policy.trim().to_lowercase().as_str() {
+			"none" => RefreshPolicy::None,
+			"wait_for" => RefreshPolicy::WaitFor,
+			"immediate" => RefreshPolicy::Immediate,
+			_ => {
+				log::warn!("Unsupported `OPENSEARCH_REFRESH_POLICY` {:?}, as default none", refresh_policy);
+				RefreshPolicy::None
+			}
+		}
+	}
+
+	fn refresh(&self) -> Refresh {
+		match self {
+			RefreshPolicy::None => Refresh::False,
+			RefreshPolicy::WaitFor => Refresh::WaitFor,
+			RefreshPolicy::Immediate => Refresh::True,
+		}
+	}
 }
 
-pub async fn create(_document: &dyn IndexDocument) -> Result<bool, std::string::String> {
-	let client: OpenSearch = create_opensearch_client()?;
+//	Make all changes of an index searchable
+pub async fn refresh_index(_client: &OpenSearch, _index_name: &str) -> Result<bool, DictionaryError> {
+	let _response: Result<Response, DictionaryError> = send_with_retry(|| async {
+		_client
+			.indices()
+			.refresh(IndicesRefreshParts::Index(&[_index_name]))
+			.send().await
+	}).await;
+	let response: Response = match _response {
+		Ok(value) => value,
+		Err(error) => {
+			log::error!("{:?}", error);
+			return Err(error);
+		}
+	};
+	if !response.status_code().is_success() {
+		return Err(DictionaryError::BackendUnavailable(format!("Error refreshing index {:?} ({:?})", _index_name, response.text().await)));
+	}
+	Ok(true)
+}
 
-	let _response: Result<bool, String> = create_index_definition(_document).await;
+pub async fn create(_client: &OpenSearch, _document: &dyn IndexDocument, _refresh: RefreshPolicy) -> Result<bool, DictionaryError> {
+	let _response: Result<bool, DictionaryError> = create_index_definition(_client, _document).await;
 	let _response: bool = match _response {
         Ok(_) => true,
         Err(error) => {
@@ -186,92 +940,469 @@ pub async fn create(_document: &dyn IndexDocument) -> Result<bool, std::string::
             false
         }
     };
-    match get_by_id(_document).await {
-        Ok(_) => {
-            match delete(_document).await {
-                Ok(_) => {},
-                Err(error) => log::error!("{:?}", error),
-            };
-        },
-        Err(_) => {},
-    };
-	// Create
-	let _response: Result<Response, opensearch::Error> = client
-        .index(IndexParts::IndexId(&_document.index_name(), &_document.id()))
-        .body(_document.data())
-		.send()
-		.await
-	;
+	// Create or overwrite, the previous document is replaced atomically
+	let index_name: String = _document.index_name();
+	let id: String = _document.id();
+	let _response: Result<Response, DictionaryError> = send_with_retry(|| async {
+		let mut request = _client
+			.index(IndexParts::IndexId(&index_name, &id))
+			.refresh(_refresh.refresh())
+			.body(_document.data())
+		;
+		if let Some(version) = _document.version() {
+			request = request
+				.version(version)
+				.version_type(VersionType::External)
+			;
+		}
+		request.send().await
+	}).await;
 	let _response: Response = match _response {
         Ok(value) => value,
         Err(error) => {
             log::error!("{:?}", error);
-            return Err(error.to_string());
+            return Err(error);
         }
     };
+    if _response.status_code() == StatusCode::CONFLICT {
+		log::warn!("Stale record rejected {:?} {:?}, version {:?} is not newer than stored version", index_name, id, _document.version());
+		return Ok(false);
+    }
     if !_response.status_code().is_success() {
-        return Err(format!("Error inserting record {:?} {:?} {:?}", _document.index_name(), _document.id(), _response.text().await));
+        return Err(DictionaryError::BackendUnavailable(format!("Error inserting record {:?} {:?} {:?}", _document.index_name(), _document.id(), _response.text().await)));
     }
     Ok(true)
 }
 
-pub async fn delete(_document: &dyn IndexDocument) -> Result<bool, std::string::String> {
-	let client: OpenSearch = create_opensearch_client()?;
-
+pub async fn delete(_client: &OpenSearch, _document: &dyn IndexDocument, _refresh: RefreshPolicy) -> Result<bool, DictionaryError> {
 	// Delete
-	let _response: Result<Response, opensearch::Error> = client
-        .delete(DeleteParts::IndexId(&_document.index_name(), &_document.id()))
-		.send()
-		.await
-	;
+	let index_name: String = _document.index_name();
+	let id: String = _document.id();
+	let _response: Result<Response, DictionaryError> = send_with_retry(|| async {
+		let mut request = _client
+			.delete(DeleteParts::IndexId(&index_name, &id))
+			.refresh(_refresh.refresh())
+		;
+		// a delete has the version of the last change of record
+		if let Some(version) = _document.version() {
+			request = request
+				.version(version)
+				.version_type(VersionType::ExternalGte)
+			;
+		}
+		request.send().await
+	}).await;
 
     match _response {
         Ok(value) => {
 			let status: StatusCode = value.status_code();
+			if status == StatusCode::CONFLICT {
+				log::warn!("Stale delete rejected {:?} {:?}, version {:?} is older than stored version", index_name, id, _document.version());
+				return Ok(false);
+			}
 			// For the ‘delete’ operation, the OpenSearch library often
 			// considers 404 as ‘success’ if the document does not exist.
 			if !status.is_success() && status.as_u16() != 404 {
 				return Err(
-					format!("Error deleting record {:?} {:?} {:?}", _document.index_name(), _document.id(), value.text().await)
+					DictionaryError::BackendUnavailable(format!("Error deleting record {:?} {:?} {:?}", _document.index_name(), _document.id(), value.text().await))
 				);
 			}
 			value
 		},
         Err(error) => {
             log::error!("{:?}", error);
-            return Err(error.to_string());
+            return Err(error);
         }
     };
     Ok(true)
 }
 
-pub async fn find(_document: &dyn IndexDocument, _search_value: String, _from: i64, _size: i64) -> Result<Vec<Value>, std::string::String> {
-	let client: OpenSearch = create_opensearch_client()?;
+//	Fields of a patch event, the null (or missing) fields are not changed
+pub fn patch_fields(_document: &dyn IndexDocument) -> Value {
+	match _document.data() {
+		Value::Object(fields) => Value::Object(
+			fields.into_iter()
+				.filter(|(_, value)| !value.is_null())
+				.collect()
+		),
+		value => value
+	}
+}
+
+//	Replace the patched fields of a patch without version (`_update`)
+const PATCH_SCRIPT: &str = "if (params.version != null && ctx._source.version != null && ctx._source.version > params.version) { ctx.op = 'noop' } else { ctx._source.putAll(params.fields) }";
+
+fn patch_body(_document: &dyn IndexDocument) -> Value {
+	json!({
+		"script": {
+			"lang": "painless",
+			"source": PATCH_SCRIPT,
+			"params": {
+				"fields": patch_fields(_document),
+				"version": _document.version()
+			}
+		}
+	})
+}
+
+//	Versioned patch applied as a write of the stored document with the patched
+//	fields, so its version is the external version of the document as in the
+//	other events (`_update` only increments the stored version)
+struct PatchedDocument<'a> {
+	document: &'a dyn IndexDocument,
+	data: Value,
+}
+
+//	Stored document with the fields of patch
+fn patched_data(_document: &dyn IndexDocument, _stored: Value) -> Value {
+	let mut data: Value = _stored;
+	if let (Some(stored_fields), Value::Object(fields)) = (data.as_object_mut(), patch_fields(_document)) {
+		stored_fields.extend(fields);
+	}
+	data
+}
 
+impl IndexDocument for PatchedDocument<'_> {
+	fn mapping(self: &Self) -> serde_json::Value {
+		self.document.mapping()
+	}
+
+	fn data(self: &Self) -> serde_json::Value {
+		self.data.to_owned()
+	}
+
+	fn index_name(self: &Self) -> String {
+		self.document.index_name()
+	}
+
+	fn id(self: &Self) -> String {
+		self.document.id()
+	}
+
+	fn find(self: &Self, _search_value: String) -> serde_json::Value {
+		self.document.find(_search_value)
+	}
+
+	fn version(self: &Self) -> Option<i64> {
+		self.document.version()
+	}
+}
+
+//	Stored document to apply a versioned patch, none when it is not found
+async fn patch_source(_client: &OpenSearch, _document: &dyn IndexDocument) -> Result<Option<Value>, DictionaryError> {
+	match get_by_id(_client, _document).await {
+		Ok(value) => Ok(Some(value)),
+		Err(DictionaryError::NotFound(_)) => Ok(None),
+		Err(error) => Err(error)
+	}
+}
+
+//	Change only the fields present in document, a missing or newer stored
+//	document is not changed and `Ok(false)` is returned. A versioned patch is
+//	written with the stored document and its version
+pub async fn patch(_client: &OpenSearch, _document: &dyn IndexDocument, _refresh: RefreshPolicy) -> Result<bool, DictionaryError> {
+	if _document.version().is_some() {
+		return match patch_source(_client, _document).await? {
+			Some(stored) => {
+				let patched: PatchedDocument = PatchedDocument {
+					document: _document,
+					data: patched_data(_document, stored),
+				};
+				create(_client, &patched, _refresh).await
+			},
+			None => {
+				log::warn!("Patch rejected {:?} {:?}, record not found", _document.index_name(), _document.id());
+				Ok(false)
+			}
+		};
+	}
+	let index_name: String = _document.index_name();
+	let id: String = _document.id();
+	let _response: Result<Response, DictionaryError> = send_with_retry(|| async {
+		_client
+			.update(UpdateParts::IndexId(&index_name, &id))
+			.retry_on_conflict(PATCH_RETRY_ON_CONFLICT)
+			.refresh(_refresh.refresh())
+			.body(patch_body(_document))
+			.send().await
+	}).await;
+	let response: Response = match _response {
+		Ok(value) => value,
+		Err(error) => {
+			log::error!("{:?}", error);
+			return Err(error);
+		}
+	};
+	let status: StatusCode = response.status_code();
+	if status == StatusCode::NOT_FOUND {
+		log::warn!("Patch rejected {:?} {:?}, record not found", index_name, id);
+		return Ok(false);
+	}
+	if !status.is_success() {
+		return Err(DictionaryError::BackendUnavailable(format!("Error patching record {:?} {:?} {:?}", index_name, id, response.text().await)));
+	}
+	let response_body: Value = match response.json::<Value>().await {
+		Ok(response) => response,
+		Err(error) => {
+			log::error!("{:?}", error);
+			return Err(error.into());
+		},
+	};
+	if response_body["result"] == "noop" {
+		log::warn!("Stale patch rejected {:?} {:?}, version {:?} is not newer than stored version", index_name, id, _document.version());
+		return Ok(false);
+	}
+	Ok(true)
+}
+
+//	Concurrent changes of the same document are retried by OpenSearch
+const PATCH_RETRY_ON_CONFLICT: i64 = 3;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum BulkAction {
+	//	Create or overwrite the document
+	Index,
+	//	Change only the fields present in the document
+	Patch,
+	Delete,
+}
+
+#[derive(Clone)]
+pub struct BulkOperation {
+	pub action: BulkAction,
+	pub document: Arc<dyn IndexDocument>,
+	//	Trace id of the message with the operation, added to its logs
+	pub trace_id: Option<String>,
+}
+
+impl BulkOperation {
+	//	Trace of logs, `-` without trace id
+	pub fn trace(&self) -> &str {
+		self.trace_id.as_deref().unwrap_or("-")
+	}
+}
+
+//	Write many documents with a single `_bulk` request, the result of each
+//	operation is returned in the same order of `_operations`, as `Ok(false)`
+//	when a versioned document is rejected because it is stale
+pub async fn bulk(_client: &OpenSearch, _operations: &[BulkOperation], _refresh: RefreshPolicy) -> Result<Vec<Result<bool, DictionaryError>>, DictionaryError> {
+	if _operations.is_empty() {
+		return Ok(Vec::new());
+	}
+	//	Index definitions
+	let mut index_names: Vec<String> = Vec::new();
+	for operation in _operations {
+		let index_name: String = operation.document.index_name();
+		if operation.action != BulkAction::Index || index_names.contains(&index_name) {
+			continue;
+		}
+		if let Err(error) = create_index_definition(_client, operation.document.as_ref()).await {
+			log::error!("{:?}", error);
+		}
+		index_names.push(index_name);
+	}
+
+	let patches: HashMap<usize, Option<Value>> = patched_sources(_client, _operations).await?;
+
+	let mut body: Vec<Value> = Vec::with_capacity(_operations.len() * 2);
+	// operations of body, a versioned patch without stored document is not sent
+	let mut sent: Vec<usize> = Vec::with_capacity(_operations.len());
+	let mut results: Vec<Result<bool, DictionaryError>> = Vec::with_capacity(_operations.len());
+	for (index, operation) in _operations.iter().enumerate() {
+		results.push(Ok(false));
+		if let Some(None) = patches.get(&index) {
+			log::warn!("Patch rejected {:?} {:?}, record not found (trace {})", operation.document.index_name(), operation.document.id(), operation.trace());
+			continue;
+		}
+		sent.push(index);
+		let mut metadata: Value = json!({
+			"_index": operation.document.index_name(),
+			"_id": operation.document.id()
+		});
+		if let Some(version) = operation.document.version() {
+			metadata["version"] = json!(version);
+			metadata["version_type"] = json!(if operation.action == BulkAction::Delete { "external_gte" } else { "external" });
+		}
+		match operation.action {
+			BulkAction::Index => {
+				body.push(json!({ "index": metadata }));
+				body.push(operation.document.data());
+			},
+			BulkAction::Patch => {
+				if let Some(Some(data)) = patches.get(&index) {
+					body.push(json!({ "index": metadata }));
+					body.push(data.to_owned());
+					continue;
+				}
+				body.push(json!({
+					"update": {
+						"_index": operation.document.index_name(),
+						"_id": operation.document.id(),
+						"retry_on_conflict": PATCH_RETRY_ON_CONFLICT
+					}
+				}));
+				body.push(patch_body(operation.document.as_ref()));
+			},
+			BulkAction::Delete => {
+				body.push(json!({ "delete": metadata }));
+			}
+		}
+	}
+	if sent.is_empty() {
+		return Ok(results);
+	}
+
+	let _response: Result<Response, DictionaryError> = send_with_retry(|| async {
+		_client
+			.bulk(BulkParts::None)
+			.refresh(_refresh.refresh())
+			// the body is built again on each attempt
+			.body(body.iter().cloned().map(JsonBody::from).collect::<Vec<JsonBody<Value>>>())
+			.send().await
+	}).await;
+	let response: Response = match _response {
+		Ok(value) => value,
+		Err(error) => {
+			log::error!("{:?}", error);
+			return Err(error);
+		}
+	};
+	if !response.status_code().is_success() {
+		return Err(DictionaryError::BackendUnavailable(format!("Error writing bulk records {:?}", response.text().await)));
+	}
+	let response_body: Value = match response.json::<Value>().await {
+		Ok(response) => response,
+		Err(error) => {
+			log::error!("{:?}", error);
+			return Err(error.into());
+		},
+	};
+
+	let items: &Vec<Value> = match response_body["items"].as_array() {
+		Some(items) if items.len() == sent.len() => items,
+		_ => return Err(DictionaryError::Deserialization(format!("Invalid bulk response, expected {} items", sent.len())))
+	};
+	for (item, index) in items.iter().zip(sent) {
+		let operation: &BulkOperation = &_operations[index];
+		results[index] = {
+			let result: &Value = match operation.action {
+				BulkAction::Index => &item["index"],
+				BulkAction::Patch if patches.contains_key(&index) => &item["index"],
+				BulkAction::Patch => &item["update"],
+				BulkAction::Delete => &item["delete"],
+			};
+			let status: u64 = result["status"].as_u64().unwrap_or(500);
+			if operation.action == BulkAction::Patch && status == 404 {
+				log::warn!("Patch rejected {:?} {:?}, record not found (trace {})", operation.document.index_name(), operation.document.id(), operation.trace());
+				Ok(false)
+			} else if operation.action == BulkAction::Patch && result["result"] == "noop" {
+				log::warn!("Stale patch rejected {:?} {:?}, version {:?} is not newer than stored version (trace {})", operation.document.index_name(), operation.document.id(), operation.document.version(), operation.trace());
+				Ok(false)
+			// a missing document on delete is not an error
+			} else if (200..300).contains(&status) || (operation.action == BulkAction::Delete && status == 404) {
+				Ok(true)
+			} else if status == 409 {
+				log::warn!("Stale record rejected {:?} {:?}, version {:?} is not newer than stored version (trace {})", operation.document.index_name(), operation.document.id(), operation.document.version(), operation.trace());
+				Ok(false)
+			} else {
+				let message: String = format!("Error writing bulk record {:?} {:?} ({}) {} (trace {})", operation.document.index_name(), operation.document.id(), status, result["error"], operation.trace());
+				// an overloaded or unavailable shard can accept the record later
+				if status == 429 || status >= 500 {
+					Err(DictionaryError::BackendUnavailable(message))
+				} else {
+					Err(DictionaryError::BadRequest(message))
+				}
+			}
+		};
+	}
+	Ok(results)
+}
+
+//	Documents to write for the versioned patches by position of operation, with
+//	the stored document and the fields of patch, none when it is not found. The
+//	previous operations of batch over the same document are applied before
+async fn patched_sources(_client: &OpenSearch, _operations: &[BulkOperation]) -> Result<HashMap<usize, Option<Value>>, DictionaryError> {
+	let mut ids_by_index: HashMap<String, Vec<String>> = HashMap::new();
+	for operation in _operations {
+		if operation.action == BulkAction::Patch && operation.document.version().is_some() {
+			let ids: &mut Vec<String> = ids_by_index.entry(operation.document.index_name()).or_default();
+			if !ids.contains(&operation.document.id()) {
+				ids.push(operation.document.id());
+			}
+		}
+	}
+	let mut documents: HashMap<(String, String), Option<Value>> = HashMap::new();
+	for (index_name, ids) in ids_by_index {
+		let sources: Vec<Option<Value>> = match get_by_ids(_client, index_name.to_owned(), &ids).await {
+			Ok(value) => value,
+			Err(DictionaryError::IndexMissing(_)) => vec![None; ids.len()],
+			Err(error) => return Err(error)
+		};
+		for (id, source) in ids.into_iter().zip(sources) {
+			documents.insert((index_name.to_owned(), id), source);
+		}
+	}
+
+	let mut patches: HashMap<usize, Option<Value>> = HashMap::new();
+	if documents.is_empty() {
+		return Ok(patches);
+	}
+	for (index, operation) in _operations.iter().enumerate() {
+		let key: (String, String) = (operation.document.index_name(), operation.document.id());
+		let document: &mut Option<Value> = match documents.get_mut(&key) {
+			Some(value) => value,
+			None => continue
+		};
+		match operation.action {
+			BulkAction::Index => *document = Some(operation.document.data()),
+			BulkAction::Delete => *document = None,
+			BulkAction::Patch => {
+				*document = document.take()
+					.map(|stored| patched_data(operation.document.as_ref(), stored))
+				;
+				if operation.document.version().is_some() {
+					patches.insert(index, document.to_owned());
+				}
+			}
+		};
+	}
+	Ok(patches)
+}
+
+//	A search over a missing index (or alias) is answered with 404
+fn search_error(_status: StatusCode, _message: String) -> DictionaryError {
+	if _status == StatusCode::NOT_FOUND {
+		return DictionaryError::IndexMissing(_message);
+	}
+	DictionaryError::from_status(_status, _message)
+}
+
+pub async fn find(_client: &OpenSearch, _document: &dyn IndexDocument, _search_value: String, _from: i64, _size: i64) -> Result<Vec<Value>, DictionaryError> {
 	// Get
-	let _response: Result<opensearch::http::response::Response, opensearch::Error> = client
-        .search(SearchParts::Index(&[&_document.index_name()]))
-        .from(_from)
-        .size(_size)
-        .body(_document.find(_search_value))
-        .send()
-		.await
-	;
+	let _response: Result<Response, DictionaryError> = send_with_retry(|| async {
+		_client
+			.search(SearchParts::Index(&[&_document.index_name()]))
+			.from(_from)
+			.size(_size)
+			.body(_document.find(_search_value.clone()))
+			.send().await
+	}).await;
 	let response: Response = match _response {
         Ok(value) => value,
         Err(error) => {
             log::error!("{:?}", error);
-            return Err(error.to_string());
+            return Err(error);
         }
     };
-    if !response.status_code().is_success() {
-        return Err(format!("Error finding record {:?}", response.text().await));
+    let status: StatusCode = response.status_code();
+    if !status.is_success() {
+        return Err(search_error(status, format!("Error finding record {:?}", response.text().await)));
     }
 	let response_body: Value = match response.json::<Value>().await {
         Ok(response) => response,
         Err(error) => {
             log::error!("{:?}", error);
-            return Err(error.to_string());
+            return Err(error.into());
         },
     };
 
@@ -283,33 +1414,32 @@ pub async fn find(_document: &dyn IndexDocument, _search_value: String, _from: i
     Ok(list)
 }
 
-pub async fn find_from_dsl_body(_index_name: String, _body: serde_json::Value, _from: i64, _size: i64) -> Result<Vec<Value>, std::string::String> {
-	let client: OpenSearch = create_opensearch_client()?;
-
+pub async fn find_from_dsl_body(_client: &OpenSearch, _index_name: String, _body: serde_json::Value, _from: i64, _size: i64) -> Result<Vec<Value>, DictionaryError> {
     //  Get
-	let _response: Result<opensearch::http::response::Response, opensearch::Error> = client
-        .search(SearchParts::Index(&[&_index_name]))
-        .from(_from)
-        .size(_size)
-        .body(_body)
-        .send()
-		.await
-	;
+	let _response: Result<Response, DictionaryError> = send_with_retry(|| async {
+		_client
+			.search(SearchParts::Index(&[&_index_name]))
+			.from(_from)
+			.size(_size)
+			.body(_body.clone())
+			.send().await
+	}).await;
 	let response: Response = match _response {
         Ok(value) => value,
         Err(error) => {
             log::error!("{:?}", error);
-            return Err(error.to_string());
+            return Err(error);
         }
     };
-    if !response.status_code().is_success() {
-        return Err(format!("Error finding record {:?}", response.text().await));
+    let status: StatusCode = response.status_code();
+    if !status.is_success() {
+        return Err(search_error(status, format!("Error finding record {:?}", response.text().await)));
     }
 	let response_body: Value = match response.json::<Value>().await {
         Ok(response) => response,
         Err(error) => {
             log::error!("{:?}", error);
-            return Err(error.to_string());
+            return Err(error.into());
         },
     };
 
@@ -324,24 +1454,267 @@ pub async fn find_from_dsl_body(_index_name: String, _body: serde_json::Value, _
     Ok(list)
 }
 
-pub async fn get_by_id(_document: &dyn IndexDocument) -> Result<Value, std::string::String> {
-	let client: OpenSearch = create_opensearch_client()?;
+//	Get the matches with `_score` and `highlight` of each hit, and the exact
+//	count of all matches (`hits.total`)
+pub async fn find_hits_from_dsl_body(_client: &OpenSearch, _index_name: String, _body: serde_json::Value, _from: i64, _size: i64) -> Result<SearchHits, DictionaryError> {
+	let _response: Result<Response, DictionaryError> = send_with_retry(|| async {
+		_client
+			.search(SearchParts::Index(&[&_index_name]))
+			.from(_from)
+			.size(_size)
+			.track_total_hits(true)
+			.body(_body.clone())
+			.send().await
+	}).await;
+	let response: Response = match _response {
+		Ok(value) => value,
+		Err(error) => {
+			log::error!("{:?}", error);
+			return Err(error);
+		}
+	};
+	let status: StatusCode = response.status_code();
+	if !status.is_success() {
+		return Err(search_error(status, format!("Error finding record {:?}", response.text().await)));
+	}
+	let response_body: Value = match response.json::<Value>().await {
+		Ok(response) => response,
+		Err(error) => {
+			log::error!("{:?}", error);
+			return Err(error.into());
+		},
+	};
+
+	let mut list: Vec<SearchHit> = Vec::new();
+	if let Some(hits) = response_body["hits"]["hits"].as_array() {
+		for hit in hits {
+			let highlight: HashMap<String, Vec<String>> = match hit.get("highlight") {
+				Some(value) => serde_json::from_value(value.to_owned())?,
+				None => HashMap::new()
+			};
+			list.push(SearchHit {
+				source: hit["_source"].to_owned(),
+				score: hit["_score"].as_f64(),
+				highlight,
+			});
+		}
+	}
+	// `{ "value": 1, "relation": "eq" }`, or a number on previous versions
+	let total: &Value = &response_body["hits"]["total"];
+	let total: i64 = total["value"].as_i64()
+		.or_else(|| total.as_i64())
+		.unwrap_or(list.len() as i64)
+	;
+	Ok(SearchHits {
+		total,
+		hits: list,
+	})
+}
+
+//	Time that search context is kept alive between scroll pages
+const SCROLL_KEEP_ALIVE: &str = "1m";
+
+pub struct SearchPage {
+	pub values: Vec<Value>,
+	//	Cursor to get the next page, none when all matches were returned
+	pub scroll_id: Option<String>,
+}
+
+//	Get a page of hits (with metadata as `_id`) of a scroll, the first page is
+//	requested without `_scroll_id` and the next pages with the returned cursor
+async fn scroll_hits(_client: &OpenSearch, _index_name: String, _body: serde_json::Value, _size: i64, _scroll_id: Option<String>) -> Result<SearchPage, DictionaryError> {
+	let _response: Result<Response, DictionaryError> = match &_scroll_id {
+		// a scroll request is not retried, the cursor can be advanced by a failed attempt
+		Some(scroll_id) => {
+			send_once(
+				_client
+					.scroll(ScrollParts::None)
+					.body(json!({
+						"scroll": SCROLL_KEEP_ALIVE,
+						"scroll_id": scroll_id
+					}))
+					.send()
+			).await
+		},
+		None => {
+			send_with_retry(|| async {
+				_client
+					.search(SearchParts::Index(&[&_index_name]))
+					.scroll(SCROLL_KEEP_ALIVE)
+					.size(_size)
+					.body(_body.clone())
+					.send().await
+			}).await
+		}
+	};
+	let response: Response = match _response {
+		Ok(value) => value,
+		Err(error) => {
+			log::error!("{:?}", error);
+			return Err(error);
+		}
+	};
+	let status: StatusCode = response.status_code();
+	if !status.is_success() {
+		return Err(search_error(status, format!("Error finding record {:?}", response.text().await)));
+	}
+	let response_body: Value = match response.json::<Value>().await {
+		Ok(response) => response,
+		Err(error) => {
+			log::error!("{:?}", error);
+			return Err(error.into());
+		},
+	};
+
+	let list: Vec<Value> = match response_body["hits"]["hits"].as_array() {
+		Some(hits) => hits.to_owned(),
+		None => Vec::new()
+	};
+	let scroll_id: Option<String> = response_body["_scroll_id"].as_str().map(|value| value.to_owned());
+	if (list.len() as i64) < _size {
+		// last page
+		if let Some(value) = scroll_id {
+			clear_scroll(_client, value).await;
+		}
+		return Ok(SearchPage {
+			values: list,
+			scroll_id: None
+		});
+	}
+	Ok(SearchPage {
+		values: list,
+		scroll_id
+	})
+}
+
+//	Get a page of matches without `max_result_window` limit, the first page is
+//	requested without `_scroll_id` and the next pages with the returned cursor
+pub async fn find_from_dsl_body_scroll(_client: &OpenSearch, _index_name: String, _body: serde_json::Value, _size: i64, _scroll_id: Option<String>) -> Result<SearchPage, DictionaryError> {
+	let page: SearchPage = scroll_hits(_client, _index_name, _body, _size, _scroll_id).await?;
+	Ok(SearchPage {
+		values: page.values.iter().map(|hit| hit["_source"].to_owned()).collect(),
+		scroll_id: page.scroll_id
+	})
+}
+
+//	Release the search context of a scroll
+pub async fn clear_scroll(_client: &OpenSearch, _scroll_id: String) {
+	let _response: Result<Response, DictionaryError> = send_with_retry(|| async {
+		_client
+			.clear_scroll(ClearScrollParts::None)
+			.body(json!({
+				"scroll_id": [_scroll_id]
+			}))
+			.send().await
+	}).await;
+	if let Err(error) = _response {
+		log::warn!("Error clearing scroll: {}", error);
+	}
+}
 
+//	Open scroll of a stream, it is cleared when it is dropped before the last
+//	page, as a failed page or a stream that is not consumed
+struct ScrollContext {
+	client: OpenSearch,
+	scroll_id: Option<String>,
+}
+
+impl ScrollContext {
+	fn take(mut self) -> Option<String> {
+		self.scroll_id.take()
+	}
+}
+
+impl Drop for ScrollContext {
+	fn drop(&mut self) {
+		if let Some(scroll_id) = self.scroll_id.take() {
+			match tokio::runtime::Handle::try_current() {
+				Ok(handle) => {
+					let client: OpenSearch = self.client.clone();
+					handle.spawn(async move { clear_scroll(&client, scroll_id).await });
+				},
+				Err(_) => log::warn!("Scroll not cleared without runtime, it expires after {}", SCROLL_KEEP_ALIVE)
+			}
+		}
+	}
+}
+
+enum ScrollState {
+	Start,
+	Next(ScrollContext),
+	Done,
+}
+
+//	Lazy stream with all the pages of matches, each page is requested when
+//	the previous one was consumed
+pub fn find_from_dsl_body_stream(_client: OpenSearch, _index_name: String, _body: serde_json::Value, _size: i64) -> impl Stream<Item = Result<Vec<Value>, DictionaryError>> {
+	stream::unfold(ScrollState::Start, move |state: ScrollState| {
+		let client: OpenSearch = _client.clone();
+		let index_name: String = _index_name.to_owned();
+		let body: Value = _body.to_owned();
+		async move {
+			// the context is kept until the page is received, to clear it when the request is dropped
+			let scroll: Option<ScrollContext> = match state {
+				ScrollState::Start => None,
+				ScrollState::Next(value) => Some(value),
+				ScrollState::Done => return None,
+			};
+			let scroll_id: Option<String> = scroll.as_ref().and_then(|context| context.scroll_id.to_owned());
+			match find_from_dsl_body_scroll(&client, index_name, body, _size, scroll_id).await {
+				Ok(page) => {
+					// the scroll of page replaces the previous one, it is cleared on last page
+					if let Some(context) = scroll {
+						context.take();
+					}
+					let next_state: ScrollState = match page.scroll_id {
+						Some(value) => ScrollState::Next(ScrollContext { client: client.clone(), scroll_id: Some(value) }),
+						None => ScrollState::Done,
+					};
+					Some((Ok(page.values), next_state))
+				},
+				Err(error) => {
+					if let Some(scroll_id) = scroll.and_then(ScrollContext::take) {
+						clear_scroll(&client, scroll_id).await;
+					}
+					Some((Err(error), ScrollState::Done))
+				},
+			}
+		}
+	})
+}
+
+//	Lazy stream with all the pages of `_document.find(_search_value)` matches
+pub fn find_stream(_client: OpenSearch, _document: &dyn IndexDocument, _search_value: String, _size: i64) -> impl Stream<Item = Result<Vec<Value>, DictionaryError>> {
+	find_from_dsl_body_stream(_client, _document.index_name(), _document.find(_search_value), _size)
+}
+
+//	Get all matches of a search, loading them by pages of `_size`
+pub async fn find_all_from_dsl_body(_client: &OpenSearch, _index_name: String, _body: serde_json::Value, _size: i64) -> Result<Vec<Value>, DictionaryError> {
+	let mut list: Vec<Value> = Vec::new();
+	let mut pages = Box::pin(find_from_dsl_body_stream(_client.clone(), _index_name, _body, _size));
+	while let Some(page) = pages.next().await {
+		list.extend(page?);
+	}
+	Ok(list)
+}
+
+pub async fn get_by_id(_client: &OpenSearch, _document: &dyn IndexDocument) -> Result<Value, DictionaryError> {
 	// Get
-	let _response: Result<Response, opensearch::Error> = client
-        .get(GetParts::IndexId(&_document.index_name(), &_document.id()))
-		.send()
-		.await
-	;
+	let _response: Result<Response, DictionaryError> = send_with_retry(|| async {
+		_client
+			.get(GetParts::IndexId(&_document.index_name(), &_document.id()))
+			.send().await
+	}).await;
 	let _response: Response = match _response {
         Ok(value) => value,
         Err(error) => {
             log::error!("{:?}", error);
-            return Err(error.to_string());
+            return Err(error);
         }
     };
-    if !_response.status_code().is_success() {
-        return Err(format!("Error finding record by ID {:?}", _response.text().await));
+    let status: StatusCode = _response.status_code();
+    if !status.is_success() {
+        return Err(DictionaryError::from_status(status, format!("Error finding record by ID {:?}", _response.text().await)));
     }
 	let response_body: Value = match _response.json::<Value>().await {
         Ok(response) => {
@@ -349,8 +1722,156 @@ pub async fn get_by_id(_document: &dyn IndexDocument) -> Result<Value, std::stri
         },
         Err(error) => {
             log::error!("{:?}", error);
-            return Err(error.to_string());
+            return Err(error.into());
         },
     };
     Ok(response_body)
 }
+
+//	Get many documents of an index in one request (`_mget`), the result has
+//	the same order of `_ids` and the missing documents are none
+pub async fn get_by_ids(_client: &OpenSearch, _index_name: String, _ids: &[String]) -> Result<Vec<Option<Value>>, DictionaryError> {
+	let _response: Result<Response, DictionaryError> = send_with_retry(|| async {
+		_client
+			.mget(MgetParts::Index(&_index_name))
+			.body(json!({
+				"ids": _ids
+			}))
+			.send().await
+	}).await;
+	let response: Response = match _response {
+		Ok(value) => value,
+		Err(error) => {
+			log::error!("{:?}", error);
+			return Err(error);
+		}
+	};
+	let status: StatusCode = response.status_code();
+	if !status.is_success() {
+		return Err(search_error(status, format!("Error finding records by IDs {:?}", response.text().await)));
+	}
+	let response_body: Value = match response.json::<Value>().await {
+		Ok(response) => response,
+		Err(error) => {
+			log::error!("{:?}", error);
+			return Err(error.into());
+		},
+	};
+
+	let docs: &Vec<Value> = match response_body["docs"].as_array() {
+		Some(docs) if docs.len() == _ids.len() => docs,
+		_ => return Err(DictionaryError::Deserialization(format!("Invalid multi get response, expected {} docs", _ids.len())))
+	};
+	let mut list: Vec<Option<Value>> = Vec::with_capacity(docs.len());
+	for doc in docs {
+		if let Some(error) = doc.get("error") {
+			return Err(DictionaryError::BackendUnavailable(format!("Error finding record by ID {:?} {}", doc["_id"], error)));
+		}
+		if doc["found"].as_bool().unwrap_or(false) {
+			list.push(Some(doc["_source"].to_owned()));
+		} else {
+			list.push(None);
+		}
+	}
+	Ok(list)
+}
+
+//	Check if the cluster answers, it fails fast while the circuit breaker is open
+pub async fn ping(_client: &OpenSearch) -> Result<bool, DictionaryError> {
+	let response: Response = send_once(
+		_client
+			.ping()
+			.send()
+	).await?;
+	let status: StatusCode = response.status_code();
+	if !status.is_success() {
+		return Err(DictionaryError::from_status(status, format!("Error checking cluster {:?}", response.text().await)));
+	}
+	Ok(true)
+}
+
+//	Dictionary store over the OpenSearch client pool created on startup
+pub struct OpenSearchStore {
+	pool: Arc<OpenSearchPool>,
+	//	Refresh of writes, searches can miss the last changes without refresh
+	refresh: RefreshPolicy,
+}
+
+impl OpenSearchStore {
+	pub fn new(_pool: Arc<OpenSearchPool>, _refresh: RefreshPolicy) -> Self {
+		Self {
+			pool: _pool,
+			refresh: _refresh,
+		}
+	}
+}
+
+#[async_trait]
+impl DictionaryStore for OpenSearchStore {
+	async fn health(&self) -> StoreHealth {
+		let message: Option<String> = match ping(self.pool.client()).await {
+			Ok(_) => None,
+			Err(error) => Some(error.message().to_owned())
+		};
+		let circuit_breaker: &CircuitBreaker = get_circuit_breaker();
+		StoreHealth {
+			storage: "opensearch".to_owned(),
+			is_available: message.is_none(),
+			circuit_breaker: Some(circuit_breaker.state()),
+			consecutive_failures: circuit_breaker.consecutive_failures(),
+			message,
+		}
+	}
+
+	async fn create_index_definition(&self, _index: &dyn IndexDocument) -> Result<bool, DictionaryError> {
+		create_index_definition(self.pool.client(), _index).await
+	}
+
+	async fn delete_index_definition(&self, _index: &dyn IndexDocument) -> Result<bool, DictionaryError> {
+		delete_index_definition(self.pool.client(), _index).await
+	}
+
+	async fn get_alias_index(&self, _alias: String) -> Result<Option<String>, DictionaryError> {
+		get_alias_index(self.pool.client(), _alias).await
+	}
+
+	async fn create(&self, _document: &dyn IndexDocument) -> Result<bool, DictionaryError> {
+		create(self.pool.client(), _document, self.refresh).await
+	}
+
+	async fn patch(&self, _document: &dyn IndexDocument) -> Result<bool, DictionaryError> {
+		patch(self.pool.client(), _document, self.refresh).await
+	}
+
+	async fn delete(&self, _document: &dyn IndexDocument) -> Result<bool, DictionaryError> {
+		delete(self.pool.client(), _document, self.refresh).await
+	}
+
+	async fn get_by_id(&self, _document: &dyn IndexDocument) -> Result<Value, DictionaryError> {
+		get_by_id(self.pool.client(), _document).await
+	}
+
+	async fn get_by_ids(&self, _index_name: String, _ids: &[String]) -> Result<Vec<Option<Value>>, DictionaryError> {
+		get_by_ids(self.pool.client(), _index_name, _ids).await
+	}
+
+	async fn find(&self, _document: &dyn IndexDocument, _search_value: String, _from: i64, _size: i64) -> Result<Vec<Value>, DictionaryError> {
+		find(self.pool.client(), _document, _search_value, _from, _size).await
+	}
+
+	async fn find_from_dsl_body(&self, _index_name: String, _body: Value, _from: i64, _size: i64) -> Result<Vec<Value>, DictionaryError> {
+		find_from_dsl_body(self.pool.client(), _index_name, _body, _from, _size).await
+	}
+
+	async fn find_hits_from_dsl_body(&self, _index_name: String, _body: Value, _from: i64, _size: i64) -> Result<SearchHits, DictionaryError> {
+		find_hits_from_dsl_body(self.pool.client(), _index_name, _body, _from, _size).await
+	}
+
+	async fn find_all_from_dsl_body(&self, _index_name: String, _body: Value, _size: i64) -> Result<Vec<Value>, DictionaryError> {
+		find_all_from_dsl_body(self.pool.client(), _index_name, _body, _size).await
+	}
+
+	async fn bulk(&self, _operations: &[BulkOperation]) -> Result<Vec<Result<bool, DictionaryError>>, DictionaryError> {
+		bulk(self.pool.client(), _operations, self.refresh).await
+	}
+}
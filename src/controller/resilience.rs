@@ -0,0 +1,278 @@
+use std::future::Future;
+use std::io::ErrorKind;
+use std::sync::{Mutex, OnceLock};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+
+use opensearch::http::response::Response;
+use salvo::http::StatusCode;
+use serde::Serialize;
+
+use crate::controller::opensearch::get_env_number;
+use crate::error::DictionaryError;
+
+pub struct RetryConfig {
+	//	Retries of a failed request, `0` disables them
+	pub retries: u64,
+	//	Wait before the first retry, it is doubled on each retry
+	pub backoff: Duration,
+	pub max_backoff: Duration,
+}
+
+impl RetryConfig {
+	pub fn from_env() -> Self {
+		RetryConfig {
+			retries: get_env_number("OPENSEARCH_RETRIES", 3),
+			backoff: Duration::from_millis(get_env_number("OPENSEARCH_RETRY_BACKOFF", 100)),
+			max_backoff: Duration::from_millis(get_env_number("OPENSEARCH_RETRY_MAX_BACKOFF", 2000)),
+		}
+	}
+
+	fn backoff(&self, _attempt: u64) -> Duration {
+		let factor: u32 = 2_u32.saturating_pow(_attempt.min(16) as u32);
+		self.backoff.saturating_mul(factor).min(self.max_backoff)
+	}
+}
+
+//	Stop calling OpenSearch after `threshold` consecutive failures, the calls
+//	fail fast while it is open and a single trial call is allowed after `open_timeout`
+pub struct CircuitBreaker {
+	threshold: u64,
+	open_timeout: Duration,
+	consecutive_failures: AtomicU64,
+	opened_at: Mutex<Option<Instant>>,
+	is_trial_running: AtomicBool,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum CircuitState {
+	Closed,
+	Open,
+	//	Waiting the result of trial call
+	HalfOpen,
+}
+
+impl CircuitBreaker {
+	pub fn new(_threshold: u64, _open_timeout: Duration) -> Self {
+		CircuitBreaker {
+			threshold: _threshold.max(1),
+			open_timeout: _open_timeout,
+			consecutive_failures: AtomicU64::new(0),
+			opened_at: Mutex::new(None),
+			is_trial_running: AtomicBool::new(false),
+		}
+	}
+
+	pub fn from_env() -> Self {
+		CircuitBreaker::new(
+			get_env_number("OPENSEARCH_CIRCUIT_BREAKER_THRESHOLD", 5),
+			Duration::from_secs(get_env_number("OPENSEARCH_CIRCUIT_BREAKER_TIMEOUT", 30))
+		)
+	}
+
+	fn opened_at(&self) -> std::sync::MutexGuard<'_, Option<Instant>> {
+		match self.opened_at.lock() {
+			Ok(value) => value,
+			Err(error) => error.into_inner()
+		}
+	}
+
+	pub fn state(&self) -> CircuitState {
+		match *self.opened_at() {
+			None => CircuitState::Closed,
+			Some(opened_at) if opened_at.elapsed() < self.open_timeout => CircuitState::Open,
+			Some(_) => CircuitState::HalfOpen,
+		}
+	}
+
+	pub fn consecutive_failures(&self) -> u64 {
+		self.consecutive_failures.load(Ordering::SeqCst)
+	}
+
+	//	Check if a call can be sent, only one trial call is allowed when half open.
+	//	The permit must be kept until the result of call is reported
+	fn try_acquire(&self) -> Result<CallPermit<'_>, DictionaryError> {
+		match self.state() {
+			CircuitState::Closed => Ok(CallPermit { trial: None }),
+			CircuitState::Open => Err(
+				DictionaryError::BackendUnavailable("OpenSearch is unavailable, circuit breaker is open".to_owned())
+			),
+			CircuitState::HalfOpen => {
+				if self.is_trial_running.swap(true, Ordering::SeqCst) {
+					return Err(
+						DictionaryError::BackendUnavailable("OpenSearch is unavailable, circuit breaker is waiting a trial call".to_owned())
+					);
+				}
+				Ok(CallPermit { trial: Some(self) })
+			}
+		}
+	}
+
+	fn on_success(&self) {
+		self.consecutive_failures.store(0, Ordering::SeqCst);
+		let mut opened_at = self.opened_at();
+		if opened_at.is_some() {
+			log::info!("OpenSearch is available, circuit breaker closed");
+		}
+		*opened_at = None;
+		self.is_trial_running.store(false, Ordering::SeqCst);
+	}
+
+	fn on_failure(&self) {
+		let failures: u64 = self.consecutive_failures.fetch_add(1, Ordering::SeqCst) + 1;
+		let mut opened_at = self.opened_at();
+		// a failed trial opens it again
+		if failures >= self.threshold || opened_at.is_some() {
+			if opened_at.is_none() {
+				log::error!("OpenSearch is unavailable after {} consecutive failures, circuit breaker opened", failures);
+			}
+			*opened_at = Some(Instant::now());
+		}
+		self.is_trial_running.store(false, Ordering::SeqCst);
+	}
+}
+
+//	Permit of a call, a trial call that is dropped before its result (as a
+//	cancelled request) allows the next trial
+struct CallPermit<'a> {
+	trial: Option<&'a CircuitBreaker>,
+}
+
+impl Drop for CallPermit<'_> {
+	fn drop(&mut self) {
+		if let Some(circuit_breaker) = self.trial {
+			circuit_breaker.is_trial_running.store(false, Ordering::SeqCst);
+		}
+	}
+}
+
+static RETRY_CONFIG: OnceLock<RetryConfig> = OnceLock::new();
+
+static CIRCUIT_BREAKER: OnceLock<CircuitBreaker> = OnceLock::new();
+
+fn get_retry_config() -> &'static RetryConfig {
+	RETRY_CONFIG.get_or_init(RetryConfig::from_env)
+}
+
+pub fn get_circuit_breaker() -> &'static CircuitBreaker {
+	CIRCUIT_BREAKER.get_or_init(CircuitBreaker::from_env)
+}
+
+//	Responses of an overloaded or unavailable cluster
+fn is_retryable_status(_status: StatusCode) -> bool {
+	matches!(_status, StatusCode::TOO_MANY_REQUESTS | StatusCode::BAD_GATEWAY | StatusCode::SERVICE_UNAVAILABLE | StatusCode::GATEWAY_TIMEOUT)
+}
+
+//	Timeouts and connection errors, as a reset connection
+fn is_retryable_error(_error: &opensearch::Error) -> bool {
+	if _error.is_timeout() {
+		return true;
+	}
+	let mut source: Option<&(dyn std::error::Error + 'static)> = std::error::Error::source(_error);
+	while let Some(error) = source {
+		if let Some(io_error) = error.downcast_ref::<std::io::Error>() {
+			return matches!(
+				io_error.kind(),
+				ErrorKind::ConnectionReset | ErrorKind::ConnectionRefused | ErrorKind::ConnectionAborted
+					| ErrorKind::BrokenPipe | ErrorKind::TimedOut | ErrorKind::UnexpectedEof
+			);
+		}
+		source = error.source();
+	}
+	false
+}
+
+//	Send a request with retries for the retryable failures, waiting an
+//	exponential backoff between them. `_send` is called on each attempt
+pub async fn send_with_retry<F, Fut>(_send: F) -> Result<Response, DictionaryError>
+where
+	F: Fn() -> Fut,
+	Fut: Future<Output = Result<Response, opensearch::Error>>
+{
+	let config: &RetryConfig = get_retry_config();
+	let circuit_breaker: &CircuitBreaker = get_circuit_breaker();
+	let mut attempt: u64 = 0;
+	loop {
+		let _permit: CallPermit = circuit_breaker.try_acquire()?;
+		match _send().await {
+			Ok(response) => {
+				let status: StatusCode = response.status_code();
+				if !is_retryable_status(status) {
+					circuit_breaker.on_success();
+					return Ok(response);
+				}
+				circuit_breaker.on_failure();
+				if attempt >= config.retries {
+					// the caller gets the response of the last attempt
+					return Ok(response);
+				}
+				log::warn!("OpenSearch responded {}, retry {} of {}", status, attempt + 1, config.retries);
+			},
+			Err(error) => {
+				// a JSON error is not a failure of cluster
+				if error.is_json() {
+					circuit_breaker.on_success();
+				} else {
+					circuit_breaker.on_failure();
+				}
+				if !is_retryable_error(&error) || attempt >= config.retries {
+					return Err(error.into());
+				}
+				log::warn!("OpenSearch request error: {}, retry {} of {}", error, attempt + 1, config.retries);
+			}
+		};
+		tokio::time::sleep(config.backoff(attempt)).await;
+		attempt += 1;
+	}
+}
+
+//	Send a request without retries, as the expensive or long requests, the
+//	circuit breaker is checked and updated
+pub async fn send_once<Fut>(_send: Fut) -> Result<Response, DictionaryError>
+where
+	Fut: Future<Output = Result<Response, opensearch::Error>>
+{
+	let circuit_breaker: &CircuitBreaker = get_circuit_breaker();
+	let _permit: CallPermit = circuit_breaker.try_acquire()?;
+	match _send.await {
+		Ok(response) => {
+			if is_retryable_status(response.status_code()) {
+				circuit_breaker.on_failure();
+			} else {
+				circuit_breaker.on_success();
+			}
+			Ok(response)
+		},
+		Err(error) => {
+			if error.is_json() {
+				circuit_breaker.on_success();
+			} else {
+				circuit_breaker.on_failure();
+			}
+			Err(error.into())
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn dropped_trial_call_allows_next_trial() {
+		let circuit_breaker: CircuitBreaker = CircuitBreaker::new(1, Duration::ZERO);
+		circuit_breaker.on_failure();
+		assert_eq!(circuit_breaker.state(), CircuitState::HalfOpen);
+
+		let permit: CallPermit = circuit_breaker.try_acquire().unwrap();
+		assert!(circuit_breaker.try_acquire().is_err());
+		// the trial call is cancelled without result
+		drop(permit);
+		assert!(circuit_breaker.try_acquire().is_ok());
+
+		let _permit: CallPermit = circuit_breaker.try_acquire().unwrap();
+		circuit_breaker.on_success();
+		assert_eq!(circuit_breaker.state(), CircuitState::Closed);
+	}
+}
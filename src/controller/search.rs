@@ -0,0 +1,290 @@
+use std::collections::HashMap;
+
+use base64::Engine;
+use base64::prelude::BASE64_URL_SAFE_NO_PAD;
+use serde::Serialize;
+use serde_json::{json, Value};
+
+use crate::controller::opensearch::IndexDocument;
+use crate::error::DictionaryError;
+
+//	Search body of a value typed by user, it is sent as text (never parsed as
+//	query syntax) to the `_fields` with boost, as `name^3`. A part of `name`
+//	is matched too, as `voice` for `Invoice`. An empty value matches all documents
+pub fn search_body(_search_value: &str, _fields: &[&str]) -> Value {
+	let search_value: &str = _search_value.trim();
+	if search_value.is_empty() {
+		return json!({
+			"query": {
+				"match_all": {}
+			}
+		});
+	}
+	json!({
+		"query": {
+			"bool": {
+				"should": [
+					{
+						"multi_match": {
+							"query": search_value,
+							"fields": _fields,
+							"type": "best_fields",
+							"operator": "and",
+							// a text value on a numeric or boolean field is not an error
+							"lenient": true
+						}
+					},
+					{
+						"wildcard": {
+							"name.keyword": {
+								"value": format!("*{}*", escape_wildcard(search_value)),
+								"case_insensitive": true
+							}
+						}
+					}
+				],
+				"minimum_should_match": 1
+			}
+		}
+	})
+}
+
+//	Escape the characters of wildcard query (`*`, `?` and `\`) in a text
+pub fn escape_wildcard(_value: &str) -> String {
+	let mut escaped: String = String::with_capacity(_value.len());
+	for character in _value.chars() {
+		if matches!(character, '*' | '?' | '\\') {
+			escaped.push('\\');
+		}
+		escaped.push(character);
+	}
+	escaped
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum SearchMode {
+	//	Words of search value on the entity fields
+	Standard,
+	//	Partial and misspelled words of `name`, as the user is typing it
+	AsYouType,
+}
+
+impl SearchMode {
+	pub fn from_value(_value: Option<&String>) -> Result<Self, DictionaryError> {
+		let value: String = match _value {
+			Some(value) => value.trim().to_lowercase(),
+			None => return Ok(SearchMode::Standard)
+		};
+		match value.as_str() {
+			"" | "standard" => Ok(SearchMode::Standard),
+			"as_you_type" => Ok(SearchMode::AsYouType),
+			_ => Err(DictionaryError::BadRequest(format!("Unsupported search mode {:?}, it can be `standard` or `as_you_type`", value)))
+		}
+	}
+}
+
+//	Search body of `name` while the user is typing it: an exact name is
+//	ranked first, then the names with the same words, the names that start
+//	with the typed words and last the names with misspelled words
+pub fn search_as_you_type_body(_search_value: &str) -> Value {
+	let search_value: &str = _search_value.trim();
+	if search_value.is_empty() {
+		return search_body(search_value, &[]);
+	}
+	json!({
+		"query": {
+			"bool": {
+				"should": [
+					{
+						"term": {
+							"name.keyword": {
+								"value": search_value,
+								"boost": 10
+							}
+						}
+					},
+					{
+						"match_phrase": {
+							"name": {
+								"query": search_value,
+								"boost": 5
+							}
+						}
+					},
+					{
+						"multi_match": {
+							"query": search_value,
+							"type": "bool_prefix",
+							"fields": [
+								"name.as_you_type",
+								"name.as_you_type._2gram",
+								"name.as_you_type._3gram"
+							],
+							"boost": 3
+						}
+					},
+					{
+						"match": {
+							"name": {
+								"query": search_value,
+								"fuzziness": "AUTO",
+								"prefix_length": 1,
+								"operator": "and"
+							}
+						}
+					}
+				],
+				"minimum_should_match": 1
+			}
+		}
+	})
+}
+
+//	Search body of a document for the search mode
+pub fn search_mode_body(_document: &dyn IndexDocument, _search_value: String, _search_mode: &SearchMode) -> Value {
+	match _search_mode {
+		SearchMode::Standard => _document.find(_search_value),
+		SearchMode::AsYouType => search_as_you_type_body(&_search_value),
+	}
+}
+
+//	Text fields with highlighted fragments of matched words
+pub const HIGHLIGHT_FIELDS: [&str; 3] = ["name", "description", "help"];
+
+//	A document found by a search, with the relevance of match and the
+//	fragments of fields with the matched words between `<em>` tags
+#[derive(Debug, Clone)]
+pub struct SearchHit {
+	pub source: Value,
+	//	None when the search is sorted or the store has not relevance
+	pub score: Option<f64>,
+	pub highlight: HashMap<String, Vec<String>>,
+}
+
+//	A page of matches with the count of all matches of search
+#[derive(Debug, Clone)]
+pub struct SearchHits {
+	pub total: i64,
+	pub hits: Vec<SearchHit>,
+}
+
+//	Entity of a list response with the details of why it matched
+#[derive(Serialize, Debug, Clone)]
+pub struct SearchMatch<T> {
+	pub record: T,
+	pub score: Option<f64>,
+	pub highlight: HashMap<String, Vec<String>>,
+}
+
+//	Value of `include_matches` query parameter, by default only entities are returned
+pub fn is_include_matches(_value: Option<&String>) -> Result<bool, DictionaryError> {
+	let value: String = match _value {
+		Some(value) => value.trim().to_lowercase(),
+		None => return Ok(false)
+	};
+	match value.as_str() {
+		"" | "false" => Ok(false),
+		"true" => Ok(true),
+		_ => Err(DictionaryError::BadRequest(format!("Unsupported include matches {:?}, it can be `true` or `false`", value)))
+	}
+}
+
+//	Add the highlight of `HIGHLIGHT_FIELDS` to a search body, any word of
+//	query (as the prefixes of search as you type) is highlighted
+pub fn with_highlight(_body: Value) -> Value {
+	let mut body: Value = _body;
+	let fields: serde_json::Map<String, Value> = HIGHLIGHT_FIELDS.iter()
+		.map(|field| (field.to_string(), json!({})))
+		.collect()
+	;
+	body["highlight"] = json!({
+		"pre_tags": ["<em>"],
+		"post_tags": ["</em>"],
+		"require_field_match": false,
+		"fields": fields
+	});
+	body
+}
+
+//	Parse the found documents as entities of a list response
+pub fn search_matches<T, F>(_hits: Vec<SearchHit>, _parse: F) -> Result<Vec<SearchMatch<T>>, DictionaryError>
+where
+	F: Fn(Value) -> Result<T, DictionaryError>
+{
+	let mut matches: Vec<SearchMatch<T>> = Vec::with_capacity(_hits.len());
+	for hit in _hits {
+		matches.push(SearchMatch {
+			record: _parse(hit.source)?,
+			score: hit.score,
+			highlight: hit.highlight,
+		});
+	}
+	Ok(matches)
+}
+
+//	Page size of list routes without `page_size`
+pub const DEFAULT_PAGE_SIZE: i64 = 10;
+
+//	Limit of `page_size`, more matches must be requested by pages
+pub const MAX_PAGE_SIZE: i64 = 100;
+
+//	Limit of `from + size` of a page, as `index.max_result_window` of OpenSearch
+pub const MAX_RESULT_WINDOW: i64 = 10000;
+
+//	Page of a list route from `page_size` and `page_token` query parameters,
+//	the token is returned as `next_page_token` of the previous page
+#[derive(Debug, Clone, PartialEq)]
+pub struct PageRequest {
+	pub from: i64,
+	pub size: i64,
+}
+
+impl PageRequest {
+	pub fn from_values(_page_size: Option<&String>, _page_token: Option<&String>) -> Result<Self, DictionaryError> {
+		let size: i64 = match _page_size.map(|value| value.trim()).filter(|value| !value.is_empty()) {
+			Some(value) => match value.parse::<i64>() {
+				Ok(size) if (1..=MAX_PAGE_SIZE).contains(&size) => size,
+				_ => return Err(DictionaryError::BadRequest(format!("Invalid page size {:?}, it must be a number from 1 to {}", value, MAX_PAGE_SIZE)))
+			},
+			None => DEFAULT_PAGE_SIZE
+		};
+		let from: i64 = match _page_token.map(|value| value.trim()).filter(|value| !value.is_empty()) {
+			Some(value) => match decode_page_token(value) {
+				Some(from) => from,
+				None => return Err(DictionaryError::BadRequest(format!("Invalid page token {:?}", value)))
+			},
+			None => 0
+		};
+		if from.saturating_add(size) > MAX_RESULT_WINDOW {
+			return Err(DictionaryError::BadRequest(format!("Page out of the first {} matches, the search must be refined", MAX_RESULT_WINDOW)));
+		}
+		Ok(PageRequest {
+			from,
+			size,
+		})
+	}
+
+	//	Token of the next page, none when this is the last page or the next
+	//	page is out of the result window
+	pub fn next_page_token(&self, _total: i64) -> Option<String> {
+		let next_from: i64 = self.from + self.size;
+		if next_from >= _total || next_from + self.size > MAX_RESULT_WINDOW {
+			return None;
+		}
+		Some(encode_page_token(next_from))
+	}
+}
+
+//	The token is opaque to clients, it is the offset of the page
+fn encode_page_token(_from: i64) -> String {
+	BASE64_URL_SAFE_NO_PAD.encode(format!("from:{}", _from))
+}
+
+fn decode_page_token(_token: &str) -> Option<i64> {
+	let bytes: Vec<u8> = BASE64_URL_SAFE_NO_PAD.decode(_token).ok()?;
+	let value: String = String::from_utf8(bytes).ok()?;
+	value.strip_prefix("from:")?
+		.parse::<i64>()
+		.ok()
+		.filter(|from| *from >= 0)
+}
@@ -0,0 +1,165 @@
+use std::env;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::controller::file::FileStore;
+use crate::controller::memory::MemoryStore;
+use crate::controller::opensearch::{BulkAction, BulkOperation, IndexDocument, OpenSearchConfig, OpenSearchPool, OpenSearchStore, RefreshPolicy};
+use crate::controller::resilience::CircuitState;
+use crate::controller::search::SearchHits;
+use crate::error::DictionaryError;
+
+#[derive(Serialize, Debug, Clone)]
+pub struct StoreHealth {
+	pub storage: String,
+	pub is_available: bool,
+	//	State of circuit breaker of backend calls, none when the store has not one
+	pub circuit_breaker: Option<CircuitState>,
+	pub consecutive_failures: u64,
+	pub message: Option<String>,
+}
+
+impl StoreHealth {
+	//	Health of a local store, it is always available
+	pub fn local(_storage: &str) -> Self {
+		StoreHealth {
+			storage: _storage.to_owned(),
+			is_available: true,
+			circuit_breaker: None,
+			consecutive_failures: 0,
+			message: None,
+		}
+	}
+}
+
+//	Storage of dictionary documents, models and Kafka consumer use it instead
+//	of a specific backend
+#[async_trait]
+pub trait DictionaryStore: Send + Sync {
+	//	Check if the backend can attend requests
+	async fn health(&self) -> StoreHealth;
+
+	//	Create the index (or alias) of `_index.index_name()` when it does not exist
+	async fn create_index_definition(&self, _index: &dyn IndexDocument) -> Result<bool, DictionaryError>;
+
+	async fn delete_index_definition(&self, _index: &dyn IndexDocument) -> Result<bool, DictionaryError>;
+
+	//	Get the physical index behind an alias, none when it does not exist
+	async fn get_alias_index(&self, _alias: String) -> Result<Option<String>, DictionaryError>;
+
+	//	Create or overwrite a document, `Ok(false)` when a versioned document is stale
+	async fn create(&self, _document: &dyn IndexDocument) -> Result<bool, DictionaryError>;
+
+	//	Change only the not null fields of document, `Ok(false)` when it does
+	//	not exist or a versioned document is stale
+	async fn patch(&self, _document: &dyn IndexDocument) -> Result<bool, DictionaryError>;
+
+	async fn delete(&self, _document: &dyn IndexDocument) -> Result<bool, DictionaryError>;
+
+	async fn get_by_id(&self, _document: &dyn IndexDocument) -> Result<Value, DictionaryError>;
+
+	//	Get many documents of an index, in the same order of `_ids` and none
+	//	for the missing documents
+	async fn get_by_ids(&self, _index_name: String, _ids: &[String]) -> Result<Vec<Option<Value>>, DictionaryError>;
+
+	async fn find(&self, _document: &dyn IndexDocument, _search_value: String, _from: i64, _size: i64) -> Result<Vec<Value>, DictionaryError> {
+		self.find_from_dsl_body(_document.index_name(), _document.find(_search_value), _from, _size).await
+	}
+
+	async fn find_from_dsl_body(&self, _index_name: String, _body: Value, _from: i64, _size: i64) -> Result<Vec<Value>, DictionaryError>;
+
+	//	Get a page of matches with score, the `highlight` requested on `_body`
+	//	and the count of all matches
+	async fn find_hits_from_dsl_body(&self, _index_name: String, _body: Value, _from: i64, _size: i64) -> Result<SearchHits, DictionaryError>;
+
+	//	Get all matches of a search, loading them by pages of `_size`
+	async fn find_all_from_dsl_body(&self, _index_name: String, _body: Value, _size: i64) -> Result<Vec<Value>, DictionaryError> {
+		let mut list: Vec<Value> = Vec::new();
+		loop {
+			let page: Vec<Value> = self.find_from_dsl_body(_index_name.to_owned(), _body.to_owned(), list.len() as i64, _size).await?;
+			let is_last_page: bool = (page.len() as i64) < _size;
+			list.extend(page);
+			if is_last_page {
+				return Ok(list);
+			}
+		}
+	}
+
+	//	Write many documents, the result of each operation is returned in the
+	//	same order of `_operations`
+	async fn bulk(&self, _operations: &[BulkOperation]) -> Result<Vec<Result<bool, DictionaryError>>, DictionaryError> {
+		let mut results: Vec<Result<bool, DictionaryError>> = Vec::with_capacity(_operations.len());
+		for operation in _operations {
+			let result: Result<bool, DictionaryError> = match operation.action {
+				BulkAction::Index => self.create(operation.document.as_ref()).await,
+				BulkAction::Patch => self.patch(operation.document.as_ref()).await,
+				BulkAction::Delete => self.delete(operation.document.as_ref()).await,
+			};
+			match &result {
+				Ok(true) => log::debug!("Document {:?} {:?} written (trace {})", operation.document.index_name(), operation.document.id(), operation.trace()),
+				Ok(false) => log::warn!("Document {:?} {:?} not written, it is stale or not found (trace {})", operation.document.index_name(), operation.document.id(), operation.trace()),
+				Err(error) => log::warn!("Document {:?} {:?} not written: {} (trace {})", operation.document.index_name(), operation.document.id(), error, operation.trace())
+			};
+			results.push(result);
+		}
+		Ok(results)
+	}
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum StorageType {
+	OpenSearch,
+	//	Documents are lost on restart, used as demo mode
+	Memory,
+	//	Documents are saved on local disk, for small installations
+	File,
+}
+
+impl StorageType {
+	pub fn from_env() -> Self {
+		let storage: String = match env::var("DICTIONARY_STORAGE") {
+			Ok(value) => value,
+			Err(_) => {
+				log::warn!("Variable `DICTIONARY_STORAGE` Not found from enviroment, as default opensearch");
+				"opensearch".to_owned()
+			}
+		};
+		match storage.trim().to_lowercase().as_str() {
+			"memory" => StorageType::Memory,
+			"file" => StorageType::File,
+			"opensearch" => StorageType::OpenSearch,
+			_ => {
+				log::warn!("Unsupported `DICTIONARY_STORAGE` {:?}, as default opensearch", storage);
+				StorageType::OpenSearch
+			}
+		}
+	}
+}
+
+//	Create the store of `_storage`, the OpenSearch store uses the client pool
+//	created on startup, or a new pool from enviroment without it
+pub fn create_dictionary_store(_storage: &StorageType, _opensearch: Option<Arc<OpenSearchPool>>) -> Result<Arc<dyn DictionaryStore>, String> {
+	match _storage {
+		StorageType::OpenSearch => {
+			let pool: Arc<OpenSearchPool> = match _opensearch {
+				Some(value) => value,
+				None => Arc::new(OpenSearchPool::new(&OpenSearchConfig::from_env())?)
+			};
+			Ok(Arc::new(OpenSearchStore::new(pool, RefreshPolicy::from_env())))
+		},
+		StorageType::Memory => Ok(Arc::new(MemoryStore::new())),
+		StorageType::File => {
+			let path: String = match env::var("DICTIONARY_STORAGE_PATH") {
+				Ok(value) => value,
+				Err(_) => {
+					log::warn!("Variable `DICTIONARY_STORAGE_PATH` Not found from enviroment, as default data");
+					"data".to_owned()
+				}
+			};
+			Ok(Arc::new(FileStore::new(&path)))
+		},
+	}
+}
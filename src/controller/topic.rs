@@ -0,0 +1,179 @@
+use std::collections::HashMap;
+use std::marker::PhantomData;
+use std::sync::{Arc, OnceLock};
+
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+
+use crate::controller::kafka::DeadLetterKind;
+use crate::controller::opensearch::{BulkAction, BulkOperation, IndexDocument};
+use crate::models::dictionary_topic_registry;
+
+//	Reason of a message that can not be applied, it is sent to dead letter topic
+#[derive(Debug, Clone)]
+pub struct TopicError {
+	pub kind: DeadLetterKind,
+	pub message: String,
+}
+
+impl TopicError {
+	pub fn deserialization(_message: String) -> Self {
+		TopicError {
+			kind: DeadLetterKind::Deserialization,
+			message: _message,
+		}
+	}
+
+	pub fn unsupported_event(_message: String) -> Self {
+		TopicError {
+			kind: DeadLetterKind::UnsupportedEvent,
+			message: _message,
+		}
+	}
+
+	pub fn unauthorized(_message: String) -> Self {
+		TopicError {
+			kind: DeadLetterKind::Unauthorized,
+			message: _message,
+		}
+	}
+
+	pub fn invalid_headers(_message: String) -> Self {
+		TopicError {
+			kind: DeadLetterKind::InvalidHeaders,
+			message: _message,
+		}
+	}
+}
+
+//	Action from event type (key of message)
+pub fn event_action(_event_type: &str) -> Option<BulkAction> {
+	if _event_type.eq("new") || _event_type.eq("update") {
+		return Some(BulkAction::Index);
+	} else if _event_type.eq("patch") {
+		return Some(BulkAction::Patch);
+	} else if _event_type.eq("delete") {
+		return Some(BulkAction::Delete);
+	}
+	None
+}
+
+//	Handler of messages from a topic
+pub trait TopicHandler: Send + Sync {
+	//	Document to index from message payload
+	fn document(&self, _payload: &str) -> Result<Arc<dyn IndexDocument>, TopicError>;
+	//	Operation to apply for an event, the event type is the key of message
+	fn operation(&self, _event_type: &str, _payload: &str) -> Result<BulkOperation, TopicError> {
+		let action: BulkAction = match event_action(_event_type) {
+			Some(value) => value,
+			None => return Err(TopicError::unsupported_event(format!("Event type {:?} not supported", _event_type)))
+		};
+		Ok(
+			BulkOperation {
+				action,
+				document: self.document(_payload)?,
+				trace_id: None
+			}
+		)
+	}
+}
+
+//	Payload of events, as `{"document": {...}}`
+#[derive(Deserialize)]
+struct DocumentEvent<T> {
+	document: Option<T>,
+}
+
+//	Handler of a topic with events of a document type
+pub struct DocumentHandler<T> {
+	document_type: PhantomData<fn() -> T>,
+}
+
+impl<T> DocumentHandler<T> {
+	pub fn new() -> Self {
+		DocumentHandler {
+			document_type: PhantomData,
+		}
+	}
+}
+
+impl<T> Default for DocumentHandler<T> {
+	fn default() -> Self {
+		DocumentHandler::new()
+	}
+}
+
+impl<T> TopicHandler for DocumentHandler<T>
+where
+	T: IndexDocument + DeserializeOwned + 'static
+{
+	fn document(&self, _payload: &str) -> Result<Arc<dyn IndexDocument>, TopicError> {
+		match serde_json::from_str::<DocumentEvent<T>>(_payload) {
+			Ok(DocumentEvent { document: Some(document) }) => Ok(Arc::new(document)),
+			Ok(DocumentEvent { document: None }) => Err(TopicError::deserialization("Document is missing".to_owned())),
+			Err(error) => Err(TopicError::deserialization(error.to_string()))
+		}
+	}
+}
+
+//	Handlers by topic name
+#[derive(Default, Clone)]
+pub struct TopicRegistry {
+	handlers: HashMap<String, Arc<dyn TopicHandler>>,
+}
+
+impl TopicRegistry {
+	pub fn new() -> Self {
+		TopicRegistry::default()
+	}
+
+	//	Add or replace the handler of a topic
+	pub fn register(mut self, _topic: &str, _handler: Arc<dyn TopicHandler>) -> Self {
+		if self.handlers.insert(_topic.to_owned(), _handler).is_some() {
+			log::warn!("Topic handler {:?} replaced", _topic);
+		}
+		self
+	}
+
+	//	Add or replace the handler of a topic with events of a document type
+	pub fn register_document<T>(self, _topic: &str) -> Self
+	where
+		T: IndexDocument + DeserializeOwned + 'static
+	{
+		self.register(_topic, Arc::new(DocumentHandler::<T>::new()))
+	}
+
+	pub fn handler(&self, _topic: &str) -> Option<Arc<dyn TopicHandler>> {
+		self.handlers.get(_topic).cloned()
+	}
+
+	pub fn topics(&self) -> Vec<String> {
+		let mut topics: Vec<String> = self.handlers.keys().cloned().collect();
+		topics.sort();
+		topics
+	}
+
+	//	Operation of a message from topic
+	pub fn operation(&self, _topic: &str, _event_type: &str, _payload: &str) -> Result<BulkOperation, TopicError> {
+		match self.handlers.get(_topic) {
+			Some(handler) => handler.operation(_event_type, _payload),
+			None => Err(TopicError::deserialization(format!("Topic {:?} not supported", _topic)))
+		}
+	}
+}
+
+static TOPIC_REGISTRY: OnceLock<TopicRegistry> = OnceLock::new();
+
+//	Set the shared registry, it should be called once on startup before the
+//	Kafka consumer is started
+pub fn init_topic_registry(_registry: TopicRegistry) -> &'static TopicRegistry {
+	if TOPIC_REGISTRY.get().is_some() {
+		log::warn!("Topic registry already initialized, skipping creation.");
+	}
+	TOPIC_REGISTRY.get_or_init(|| _registry)
+}
+
+//	Shared registry, with the dictionary topics when it is not initialized
+pub fn get_topic_registry() -> &'static TopicRegistry {
+	TOPIC_REGISTRY.get_or_init(dictionary_topic_registry)
+}
@@ -0,0 +1,89 @@
+use std::fmt;
+
+use salvo::http::StatusCode;
+
+//	Errors of dictionary operations, handlers use it to answer with the right
+//	HTTP status and a stable error code
+#[derive(Debug, Clone, PartialEq)]
+pub enum DictionaryError {
+	//	Record not found, as a window without the requested id
+	NotFound(String),
+	//	Missing or invalid request parameters
+	BadRequest(String),
+	//	Index (or alias) not found for language, client or dictionary code
+	IndexMissing(String),
+	//	OpenSearch can not be reached or it can not attend the request
+	BackendUnavailable(String),
+	//	Stored or received value can not be parsed
+	Deserialization(String),
+	//	Missing or invalid token of a protected request
+	Unauthorized(String),
+}
+
+impl DictionaryError {
+	//	Code returned to clients, it must not be changed
+	pub fn code(&self) -> &'static str {
+		match self {
+			DictionaryError::NotFound(_) => "not_found",
+			DictionaryError::BadRequest(_) => "bad_request",
+			DictionaryError::IndexMissing(_) => "index_missing",
+			DictionaryError::BackendUnavailable(_) => "backend_unavailable",
+			DictionaryError::Deserialization(_) => "deserialization_error",
+			DictionaryError::Unauthorized(_) => "unauthorized",
+		}
+	}
+
+	pub fn status_code(&self) -> StatusCode {
+		match self {
+			DictionaryError::NotFound(_) => StatusCode::NOT_FOUND,
+			DictionaryError::BadRequest(_) => StatusCode::BAD_REQUEST,
+			DictionaryError::IndexMissing(_) => StatusCode::NOT_FOUND,
+			DictionaryError::BackendUnavailable(_) => StatusCode::SERVICE_UNAVAILABLE,
+			DictionaryError::Deserialization(_) => StatusCode::INTERNAL_SERVER_ERROR,
+			DictionaryError::Unauthorized(_) => StatusCode::UNAUTHORIZED,
+		}
+	}
+
+	//	Error of an unsuccessful OpenSearch response
+	pub fn from_status(_status: StatusCode, _message: String) -> Self {
+		match _status {
+			StatusCode::BAD_REQUEST => DictionaryError::BadRequest(_message),
+			StatusCode::NOT_FOUND => DictionaryError::NotFound(_message),
+			_ => DictionaryError::BackendUnavailable(_message),
+		}
+	}
+
+	pub fn message(&self) -> &str {
+		match self {
+			DictionaryError::NotFound(message)
+			| DictionaryError::BadRequest(message)
+			| DictionaryError::IndexMissing(message)
+			| DictionaryError::BackendUnavailable(message)
+			| DictionaryError::Deserialization(message)
+			| DictionaryError::Unauthorized(message) => message,
+		}
+	}
+}
+
+impl fmt::Display for DictionaryError {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		write!(f, "{}", self.message())
+	}
+}
+
+impl std::error::Error for DictionaryError {}
+
+impl From<opensearch::Error> for DictionaryError {
+	fn from(error: opensearch::Error) -> Self {
+		if error.is_json() {
+			return DictionaryError::Deserialization(error.to_string());
+		}
+		DictionaryError::BackendUnavailable(error.to_string())
+	}
+}
+
+impl From<serde_json::Error> for DictionaryError {
+	fn from(error: serde_json::Error) -> Self {
+		DictionaryError::Deserialization(error.to_string())
+	}
+}
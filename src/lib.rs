@@ -1,5 +1,6 @@
 pub mod models;
 pub mod controller;
+pub mod error;
 
 // #[cfg(test)]
 // mod tests {
@@ -1,9 +1,11 @@
 use salvo::macros::Extractible;
 use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
-use std::{io::ErrorKind, io::Error};
 
-use crate::{controller::opensearch::{IndexDocument, find, get_by_id}, models::{generic::DependendField, get_index_name}};
+use crate::{controller::{opensearch::IndexDocument, store::DictionaryStore}, models::{generic::DependendField, get_by_ids, get_index_name, BatchValues}};
+use crate::controller::analysis::{analysis_settings, index_analyzer};
+use crate::controller::search::{is_include_matches, search_body, search_matches, search_mode_body, with_highlight, PageRequest, SearchHits, SearchMatch, SearchMode};
+use crate::error::DictionaryError;
 
 #[derive(Deserialize, Extractible, Debug, Clone)]
 #[salvo(extract(default_source(from = "body")))]
@@ -18,7 +20,22 @@ pub struct BrowserResponse {
 
 #[derive(Serialize, Debug, Clone)]
 pub struct BrowserListResponse {
-    pub browsers: Option<Vec<Browser>>
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub browsers: Option<Vec<Browser>>,
+    //	Entities with score and highlight, instead of `browsers` when `include_matches` is requested
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub matches: Option<Vec<SearchMatch<Browser>>>,
+    //	Count of all matches of search
+    pub total: i64,
+    //	Token of `page_token` to get the next page, none on the last page
+    pub next_page_token: Option<String>
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct BrowserBatchResponse {
+	pub browsers: Vec<Browser>,
+	//	Requested ids without browser
+	pub missing_ids: Vec<String>
 }
 
 impl Default for BrowserResponse {
@@ -67,6 +84,7 @@ pub struct Browser {
     pub client_id: Option<String>,
     pub role_id: Option<String>,
     pub user_id: Option<String>,
+    pub version: Option<i64>,
 	// External Reference
 	pub context_column_names: Option<Vec<String>>,
 	pub process_id: Option<i32>,
@@ -165,6 +183,7 @@ impl Default for Browser {
 			client_id: None,
             role_id: None,
 			user_id: None,
+			version: None,
 			// External Reference
 			context_column_names: None,
 			process_id: None,
@@ -198,7 +217,9 @@ impl Browser {
 
 impl IndexDocument for Browser {
 	fn mapping(self: &Self) -> serde_json::Value {
+		let analyzer: String = index_analyzer(&self.index_name());
 		json!({
+			"settings": analysis_settings(),
 			"mappings": {
 				"properties": {
 					"uuid": { "type": "keyword" },
@@ -207,12 +228,14 @@ impl IndexDocument for Browser {
 					"code": { "type": "keyword" },
 					"name": {
 						"type": "text",
+						"analyzer": analyzer,
 						"fields": {
-							"keyword": { "type": "keyword" }
+							"keyword": { "type": "keyword" },
+							"as_you_type": { "type": "search_as_you_type", "analyzer": analyzer }
 						}
 					},
-					"description": { "type": "text" },
-					"help": { "type": "text" }
+					"description": { "type": "text", "analyzer": analyzer },
+					"help": { "type": "text", "analyzer": analyzer }
 				}
 			}
 		})
@@ -229,6 +252,10 @@ impl IndexDocument for Browser {
 		})
 	}
 
+	fn version(self: &Self) -> Option<i64> {
+		self.version
+	}
+
 	fn index_name(self: &Self) -> String {
 		match &self.index_value {
 			Some(value) => value.to_string(),
@@ -237,17 +264,7 @@ impl IndexDocument for Browser {
 	}
 
 	fn find(self: &Self, _search_value: String) -> serde_json::Value {
-		let mut query: String = "*".to_owned();
-		query.push_str(&_search_value.to_owned());
-		query.push_str(&"*".to_owned());
-
-		json!({
-			"query": {
-				"query_string": {
-					"query": query
-				}
-			}
-		})
+		search_body(&_search_value, &["name^3", "code^2", "description", "help"])
 	}
 }
 
@@ -288,33 +305,34 @@ pub struct Table {
 }
 
 
-pub fn parse_browser(value: Value) -> Browser {
-	let mut browser: Browser = serde_json::from_value(value).unwrap();
+pub fn parse_browser(value: Value) -> Result<Browser, DictionaryError> {
+	let mut browser: Browser = serde_json::from_value(value)?;
 
 	// sort fields by sequence
 	if let Some(ref mut fields) = browser.fields {
 		fields.sort_by_key(|field| field.sequence.clone().unwrap_or(0));
 	}
-	browser.to_owned()
+	Ok(browser.to_owned())
 }
 
 
 pub async fn browser_from_id(
+	_store: &dyn DictionaryStore,
 	_id: Option<String>,
 	_language: Option<&String>,
 	_dictionary_code: Option<&String>
-) -> Result<Browser, String> {
+) -> Result<Browser, DictionaryError> {
 	if _id.is_none() || _id.as_deref().map_or(false, |s| s.trim().is_empty()) {
 		return Err(
-			Error::new(ErrorKind::InvalidData.into(), "Browser Identifier is Mandatory").to_string()
+			DictionaryError::BadRequest("Browser Identifier is Mandatory".to_owned())
 		);
 	}
 
-	let _index_name: String = match get_index_name("browser".to_string(),_language, _dictionary_code).await {
+	let _index_name: String = match get_index_name(_store, "browser".to_string(),_language, _dictionary_code).await {
 		Ok(index_name) => index_name,
 		Err(error) => {
 			log::error!("Browser index name error: {:?}", error.to_string());
-			return Err(error.to_string())
+			return Err(error)
 		}
 	};
 	log::debug!("Browser index to search {:}", _index_name);
@@ -322,9 +340,9 @@ pub async fn browser_from_id(
 	let mut _document: Browser = Browser::from_id(_id);
     _document.index_value = Some(_index_name);
     let _browser_document: &dyn IndexDocument = &_document;
-    match get_by_id(_browser_document).await {
+    match _store.get_by_id(_browser_document).await {
         Ok(value) => {
-			let browser: Browser = parse_browser(value);
+			let browser: Browser = parse_browser(value)?;
 			log::debug!("Finded Browser {:?}: {:?}", browser.name, browser.id);
 			Ok(browser)
         },
@@ -336,22 +354,49 @@ pub async fn browser_from_id(
 }
 
 
+pub async fn browsers_from_ids(
+	_store: &dyn DictionaryStore,
+	_ids: Option<Vec<String>>,
+	_language: Option<&String>,
+	_dictionary_code: Option<&String>
+) -> Result<BrowserBatchResponse, DictionaryError> {
+	let batch: BatchValues = get_by_ids(_store, "browser".to_string(), _ids, _language, _dictionary_code).await?;
+	let mut browsers_list: Vec<Browser> = Vec::with_capacity(batch.values.len());
+	for value in batch.values {
+		browsers_list.push(parse_browser(value)?);
+	}
+	Ok(BrowserBatchResponse {
+		browsers: browsers_list,
+		missing_ids: batch.missing_ids
+	})
+}
+
+#[allow(clippy::too_many_arguments)]
 pub async fn browsers(
+	_store: &dyn DictionaryStore,
 	_language: Option<&String>,
 	_search_value: Option<&String>,
+	_search_mode: Option<&String>,
+	_include_matches: Option<&String>,
+	_page_size: Option<&String>,
+	_page_token: Option<&String>,
 	_dictionary_code: Option<&String>
-) -> Result<BrowserListResponse, std::io::Error> {
+) -> Result<BrowserListResponse, DictionaryError> {
 	let _search_value: String = match _search_value {
         Some(value) => value.clone(),
         None => "".to_owned()
     };
 
+	let _search_mode: SearchMode = SearchMode::from_value(_search_mode)?;
+	let _include_matches: bool = is_include_matches(_include_matches)?;
+	let _page: PageRequest = PageRequest::from_values(_page_size, _page_token)?;
+
 	//  Find index
-	let _index_name: String = match get_index_name("browser".to_string(), _language, _dictionary_code).await {
+	let _index_name: String = match get_index_name(_store, "browser".to_string(), _language, _dictionary_code).await {
 		Ok(index_name) => index_name,
 		Err(error) => {
 			log::error!("Browser index name error: {:?}", error.to_string());
-			return Err(Error::new(ErrorKind::InvalidData.into(), error))
+			return Err(error)
 		}
 	};
 	log::debug!("Browser index to search {:}", _index_name);
@@ -359,18 +404,36 @@ pub async fn browsers(
 	let mut _document: Browser = Browser::default();
     _document.index_value = Some(_index_name);
     let _browser_document: &dyn IndexDocument = &_document;
-    match find(_browser_document, _search_value, 0, 10).await {
-        Ok(values) => {
-			let mut browsers_list: Vec<Browser> = vec![];
-            for value in values {
-				let browser: Browser = parse_browser(value);
-
-                browsers_list.push(browser.to_owned());
-            }
-            Ok(BrowserListResponse {
-                browsers: Some(browsers_list)
-            })
-        },
-        Err(error) => Err(Error::new(ErrorKind::InvalidData.into(), error))
+    let mut _body: Value = search_mode_body(_browser_document, _search_value, &_search_mode);
+    if _include_matches {
+        _body = with_highlight(_body);
+    }
+    let result: SearchHits = match _store.find_hits_from_dsl_body(_browser_document.index_name(), _body, _page.from, _page.size).await {
+        Ok(value) => value,
+        Err(error) => {
+            log::error!("{}", error);
+            return Err(error)
+        }
+    };
+    let next_page_token: Option<String> = _page.next_page_token(result.total);
+    if _include_matches {
+        return Ok(BrowserListResponse {
+            browsers: None,
+            matches: Some(search_matches(result.hits, parse_browser)?),
+            total: result.total,
+            next_page_token
+        });
+    }
+
+    let mut browsers_list: Vec<Browser> = vec![];
+    for hit in result.hits {
+        let browser: Browser = parse_browser(hit.source)?;
+        browsers_list.push(browser);
     }
+    Ok(BrowserListResponse {
+        browsers: Some(browsers_list),
+        matches: None,
+        total: result.total,
+        next_page_token
+    })
 }
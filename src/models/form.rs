@@ -1,9 +1,11 @@
 use salvo::macros::Extractible;
 use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
-use std::{io::ErrorKind, io::Error};
 
-use crate::{controller::opensearch::{find, get_by_id, IndexDocument}, models::get_index_name};
+use crate::{controller::{opensearch::IndexDocument, store::DictionaryStore}, models::{get_by_ids, get_index_name, BatchValues}};
+use crate::controller::analysis::{analysis_settings, index_analyzer};
+use crate::controller::search::{is_include_matches, search_body, search_matches, search_mode_body, with_highlight, PageRequest, SearchHits, SearchMatch, SearchMode};
+use crate::error::DictionaryError;
 
 #[derive(Deserialize, Extractible, Debug, Clone)]
 #[salvo(extract(default_source(from = "body")))]
@@ -18,7 +20,22 @@ pub struct FormResponse {
 
 #[derive(Serialize, Debug, Clone)]
 pub struct FormsListResponse {
-	pub forms: Option<Vec<Form>>
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub forms: Option<Vec<Form>>,
+	//	Entities with score and highlight, instead of `forms` when `include_matches` is requested
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub matches: Option<Vec<SearchMatch<Form>>>,
+	//	Count of all matches of search
+	pub total: i64,
+	//	Token of `page_token` to get the next page, none on the last page
+	pub next_page_token: Option<String>
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct FormBatchResponse {
+	pub forms: Vec<Form>,
+	//	Requested ids without form
+	pub missing_ids: Vec<String>
 }
 
 impl Default for FormResponse {
@@ -45,7 +62,8 @@ pub struct Form {
 	pub language: Option<String>,
 	pub client_id: Option<String>,
 	pub role_id: Option<String>,
-	pub user_id: Option<String>
+	pub user_id: Option<String>,
+	pub version: Option<i64>
 }
 
 impl Default for Form {
@@ -65,7 +83,8 @@ impl Default for Form {
 			language: None,
 			client_id: None,
 			role_id: None,
-			user_id: None
+			user_id: None,
+			version: None
 		}
 	}
 }
@@ -84,7 +103,9 @@ impl Form {
 
 impl IndexDocument for Form {
 	fn mapping(self: &Self) -> serde_json::Value {
+		let analyzer: String = index_analyzer(&self.index_name());
 		json!({
+			"settings": analysis_settings(),
 			"mappings": {
 				"properties": {
 					"uuid": { "type": "keyword" },
@@ -93,12 +114,14 @@ impl IndexDocument for Form {
 					"file_name": { "type": "keyword" },
 					"name": {
 						"type": "text",
+						"analyzer": analyzer,
 						"fields": {
-							"keyword": { "type": "keyword" }
+							"keyword": { "type": "keyword" },
+							"as_you_type": { "type": "search_as_you_type", "analyzer": analyzer }
 						}
 					},
-					"description": { "type": "text" },
-					"help": { "type": "text" },
+					"description": { "type": "text", "analyzer": analyzer },
+					"help": { "type": "text", "analyzer": analyzer },
 					"is_active": { "type": "boolean" },
 					"is_beta_functionality": { "type": "boolean" }
 				}
@@ -117,6 +140,10 @@ impl IndexDocument for Form {
 		})
 	}
 
+	fn version(self: &Self) -> Option<i64> {
+		self.version
+	}
+
 	fn index_name(self: &Self) -> String {
 		match &self.index_value {
 			Some(value) => value.to_string(),
@@ -125,43 +152,34 @@ impl IndexDocument for Form {
 	}
 
 	fn find(self: &Self, _search_value: String) -> serde_json::Value {
-		let mut query: String = "*".to_owned();
-		query.push_str(&_search_value.to_owned());
-		query.push_str(&"*".to_owned());
-
-		json!({
-			"query": {
-				"query_string": {
-					"query": query
-				}
-			}
-		})
+		search_body(&_search_value, &["name^3", "description", "help"])
 	}
 }
 
 
-pub fn parse_form(value: Value) -> Form {
-	let form: Form = serde_json::from_value(value).unwrap();
-	form.to_owned()
+pub fn parse_form(value: Value) -> Result<Form, DictionaryError> {
+	let form: Form = serde_json::from_value(value)?;
+	Ok(form.to_owned())
 }
 
 
 pub async fn form_from_id(
+	_store: &dyn DictionaryStore,
 	_id: Option<String>,
 	_language: Option<&String>,
 	_dictionary_code: Option<&String>
-) -> Result<Form, String> {
+) -> Result<Form, DictionaryError> {
 	if _id.is_none() || _id.as_deref().map_or(false, |s| s.trim().is_empty()) {
 		return Err(
-			Error::new(ErrorKind::InvalidData.into(), "Form Identifier is Mandatory").to_string()
+			DictionaryError::BadRequest("Form Identifier is Mandatory".to_owned())
 		);
 	}
 
-	let _index_name: String = match get_index_name("form".to_string(), _language, _dictionary_code).await {
+	let _index_name: String = match get_index_name(_store, "form".to_string(), _language, _dictionary_code).await {
 		Ok(index_name) => index_name,
 		Err(error) => {
 			log::error!("Form index name error: {:?}", error.to_string());
-			return Err(error.to_string())
+			return Err(error)
 		}
 	};
 	log::debug!("Form index to search {:}", _index_name);
@@ -169,9 +187,9 @@ pub async fn form_from_id(
 	let mut _document: Form = Form::from_id(_id);
 	_document.index_value = Some(_index_name);
 	let _form_document: &dyn IndexDocument = &_document;
-	match get_by_id(_form_document).await {
+	match _store.get_by_id(_form_document).await {
 		Ok(value) => {
-			let form: Form = parse_form(value);
+			let form: Form = parse_form(value)?;
 			log::debug!("Finded Form {:?} Value: {:?}", form.name, form.id);
 			Ok(form)
 		},
@@ -183,22 +201,49 @@ pub async fn form_from_id(
 }
 
 
+pub async fn forms_from_ids(
+	_store: &dyn DictionaryStore,
+	_ids: Option<Vec<String>>,
+	_language: Option<&String>,
+	_dictionary_code: Option<&String>
+) -> Result<FormBatchResponse, DictionaryError> {
+	let batch: BatchValues = get_by_ids(_store, "form".to_string(), _ids, _language, _dictionary_code).await?;
+	let mut forms_list: Vec<Form> = Vec::with_capacity(batch.values.len());
+	for value in batch.values {
+		forms_list.push(parse_form(value)?);
+	}
+	Ok(FormBatchResponse {
+		forms: forms_list,
+		missing_ids: batch.missing_ids
+	})
+}
+
+#[allow(clippy::too_many_arguments)]
 pub async fn forms(
+	_store: &dyn DictionaryStore,
 	_language: Option<&String>,
 	_search_value: Option<&String>,
+	_search_mode: Option<&String>,
+	_include_matches: Option<&String>,
+	_page_size: Option<&String>,
+	_page_token: Option<&String>,
 	_dictionary_code: Option<&String>
-) -> Result<FormsListResponse, std::io::Error> {
+) -> Result<FormsListResponse, DictionaryError> {
 	let _search_value: String = match _search_value {
 		Some(value) => value.clone(),
 		None => "".to_owned()
 	};
 
+	let _search_mode: SearchMode = SearchMode::from_value(_search_mode)?;
+	let _include_matches: bool = is_include_matches(_include_matches)?;
+	let _page: PageRequest = PageRequest::from_values(_page_size, _page_token)?;
+
 	//  Find index
-	let _index_name: String = match get_index_name("form".to_string(),_language, _dictionary_code).await {
+	let _index_name: String = match get_index_name(_store, "form".to_string(),_language, _dictionary_code).await {
 		Ok(index_name) => index_name,
 		Err(error) => {
 			log::error!("Form index name error: {:?}", error.to_string());
-			return Err(Error::new(ErrorKind::InvalidData.into(), error))
+			return Err(error)
 		}
 	};
 	log::debug!("Form index to search {:}", _index_name);
@@ -206,17 +251,36 @@ pub async fn forms(
 	let mut _document: Form = Form::default();
 	_document.index_value = Some(_index_name);
 	let _forms_document: &dyn IndexDocument = &_document;
-	match find(_forms_document, _search_value, 0, 10).await {
-		Ok(values) => {
-			let mut forms_list: Vec<Form> = vec![];
-			for value in values {
-				let form: Form = parse_form(value);
-				forms_list.push(form.to_owned());
-			}
-			Ok(FormsListResponse {
-				forms: Some(forms_list)
-			})
-		},
-		Err(error) => Err(Error::new(ErrorKind::InvalidData.into(), error))
+	let mut _body: Value = search_mode_body(_forms_document, _search_value, &_search_mode);
+	if _include_matches {
+		_body = with_highlight(_body);
+	}
+	let result: SearchHits = match _store.find_hits_from_dsl_body(_forms_document.index_name(), _body, _page.from, _page.size).await {
+		Ok(value) => value,
+		Err(error) => {
+			log::error!("{}", error);
+			return Err(error)
+		}
+	};
+	let next_page_token: Option<String> = _page.next_page_token(result.total);
+	if _include_matches {
+		return Ok(FormsListResponse {
+			forms: None,
+			matches: Some(search_matches(result.hits, parse_form)?),
+			total: result.total,
+			next_page_token
+		});
+	}
+
+	let mut forms_list: Vec<Form> = vec![];
+	for hit in result.hits {
+		let form: Form = parse_form(hit.source)?;
+		forms_list.push(form);
 	}
+	Ok(FormsListResponse {
+		forms: Some(forms_list),
+		matches: None,
+		total: result.total,
+		next_page_token
+	})
 }
@@ -1,10 +1,11 @@
 use serde::{Deserialize, Serialize};
 use salvo::prelude::*;
-use std::{io::ErrorKind, io::Error};
 
+use crate::controller::store::DictionaryStore;
 use crate::models::{menu_item::menu_items_from_role, menu_tree::menu_tree_from_id, role::role_from_id};
 
 use super::{menu_item::MenuItem, menu_tree::MenuTree, role::Role};
+use crate::error::DictionaryError;
 
 #[derive(Deserialize, Extractible, Debug, Clone)]
 #[salvo(extract(default_source(from = "body")))]
@@ -145,42 +146,43 @@ impl Menu {
 
 
 pub async fn allowed_menu(
+	_store: &dyn DictionaryStore,
 	_language: Option<&String>,
 	_client_id: Option<&String>,
 	_role_id: Option<&String>,
 	_dictionary_code: Option<&String>
-) -> Result<MenuListResponse, std::io::Error> {
-	let _expected_role: Result<Role, String> = role_from_id(_role_id, _client_id, _dictionary_code).await;
+) -> Result<MenuListResponse, DictionaryError> {
+	let _expected_role: Result<Role, DictionaryError> = role_from_id(_store, _role_id, _client_id, _dictionary_code).await;
 	let _role: Role = match _expected_role {
         Ok(role) => role,
-        Err(error) => return Err(Error::new(ErrorKind::InvalidData.into(), error))
+        Err(error) => return Err(error)
     };
 	log::debug!("Loading allowed menu for role {:?} = {:?} , Tree UUID {:?}", _role.name, _role.internal_id, _role.tree_uuid);
 
 	if _role.tree_id.is_none() {
 		log::error!("Tree ID not found, on role {:?} = {:?}", _role.name, _role.internal_id);
 		return Err(
-			Error::new(ErrorKind::InvalidData.into(), "Tree ID not found")
+			DictionaryError::NotFound("Tree ID not found".to_owned())
 		)
 	}
 	if _role.tree_uuid.is_none() || _role.tree_uuid.as_deref().map_or(false, |s| s.trim().is_empty()) {
 		log::error!("Tree UUID not found, on role {:?} = {:?}", _role.name, _role.internal_id);
 		return Err(
-			Error::new(ErrorKind::InvalidData.into(), "Tree UUID not found")
+			DictionaryError::NotFound("Tree UUID not found".to_owned())
 		)
 	}
 
-	let _menu_items: Result<Vec<MenuItem>, Error> = menu_items_from_role(_role.to_owned(), _language, _dictionary_code, None, None).await;
+	let _menu_items: Result<Vec<MenuItem>, DictionaryError> = menu_items_from_role(_store, _role.to_owned(), _language, _dictionary_code, None, None).await;
 	let _menu_items: Vec<MenuItem> = match _menu_items {
 		Ok(menu) => menu,
-		Err(error) => return Err(Error::new(ErrorKind::InvalidData.into(), error))
+		Err(error) => return Err(error)
 	};
 	log::debug!("Loading allowed menu_items, total: {:?}", _menu_items.len());
 
-	let _tree_result: Result<MenuTree, Error> = menu_tree_from_id(_role.tree_uuid, _dictionary_code).await;
+	let _tree_result: Result<MenuTree, DictionaryError> = menu_tree_from_id(_store, _role.tree_uuid, _dictionary_code).await;
 	let _tree: MenuTree = match _tree_result {
         Ok(tree) => tree,
-        Err(error) => return Err(Error::new(ErrorKind::InvalidData.into(), error))
+        Err(error) => return Err(error)
     };
 	log::debug!("Loading menu tree {:?}", _tree);
 
@@ -1,11 +1,13 @@
 use salvo::macros::Extractible;
 use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
-use std::{io::ErrorKind, io::Error};
 
-use crate::controller::opensearch::{find_from_dsl_body, IndexDocument};
+use crate::controller::{opensearch::IndexDocument, store::DictionaryStore};
 
 use super::{get_index_name, menu::MenuAction, role::Role};
+use crate::controller::analysis::{analysis_settings, index_analyzer};
+use crate::controller::search::search_body;
+use crate::error::DictionaryError;
 
 #[derive(Deserialize, Extractible, Debug, Clone)]
 #[salvo(extract(default_source(from = "body")))]
@@ -49,6 +51,7 @@ pub struct MenuItem {
     pub client_id: Option<String>,
     pub role_id: Option<String>,
     pub user_id: Option<String>,
+    pub version: Option<i64>,
     // Supported References
 	pub action: Option<String>,
 	pub action_id: Option<i32>,
@@ -84,6 +87,7 @@ impl Default for MenuItem {
             client_id: None,
             role_id: None,
             user_id: None,
+            version: None,
             // Supported References
 			action: None,
 			action_id: None,
@@ -291,7 +295,9 @@ impl MenuItem {
 
 impl IndexDocument for MenuItem {
 	fn mapping(self: &Self) -> serde_json::Value {
+		let analyzer: String = index_analyzer(&self.index_name());
 		json!({
+			"settings": analysis_settings(),
 			"mappings": {
 				"properties": {
 					"uuid": { "type": "keyword" },
@@ -301,11 +307,13 @@ impl IndexDocument for MenuItem {
 					"sequence": { "type": "integer" },
 					"name": {
 						"type": "text",
+						"analyzer": analyzer,
 						"fields": {
-							"keyword": { "type": "keyword" }
+							"keyword": { "type": "keyword" },
+							"as_you_type": { "type": "search_as_you_type", "analyzer": analyzer }
 						}
 					},
-					"description": { "type": "text" },
+					"description": { "type": "text", "analyzer": analyzer },
 					"action": { "type": "keyword" },
 					"action_id": { "type": "integer" },
 					"action_uuid": { "type": "keyword" },
@@ -332,6 +340,10 @@ impl IndexDocument for MenuItem {
 		})
 	}
 
+	fn version(self: &Self) -> Option<i64> {
+		self.version
+	}
+
 	fn index_name(self: &Self) -> String {
 		match &self.index_value {
 			Some(value) => value.to_string(),
@@ -340,39 +352,42 @@ impl IndexDocument for MenuItem {
 	}
 
 	fn find(self: &Self, _search_value: String) -> serde_json::Value {
-		let mut query: String = "*".to_owned();
-		query.push_str(&_search_value.to_owned());
-		query.push_str(&"*".to_owned());
-
-		json!({
-			"query": {
-				"query_string": {
-					"query": query
-				}
-			}
-		})
+		search_body(&_search_value, &["name^3", "description"])
 	}
 }
 
 
 pub async fn menu_items_from_role(
+	_store: &dyn DictionaryStore,
 	_role: Role,
 	_language: Option<&String>,
 	_dictionary_code: Option<&String>,
 	_page_number: Option<i64>,
 	_page_size: Option<i64>
-) -> Result<Vec<MenuItem>, std::io::Error> {
+) -> Result<Vec<MenuItem>, DictionaryError> {
 	let mut _search_body: Value = MenuItem::get_find_body_from_role(_role);
-	let _index_name: String = match get_index_name("menu_item".to_string(), _language,_dictionary_code).await {
+	let _index_name: String = match get_index_name(_store, "menu_item".to_string(), _language,_dictionary_code).await {
 		Ok(index_name) => index_name,
 		Err(error) => {
 			log::error!("Menu Item index name error: {:?}", error.to_string());
-			return Err(Error::new(ErrorKind::InvalidData.into(), error))
+			return Err(error)
 		}
 	};
 	log::debug!("Menu Item index to search {:}", _index_name);
 
 	// pagination
+	if _page_number.is_none() && _page_size.is_none() {
+		// all allowed menu items, without result window limit
+		return match _store.find_all_from_dsl_body(_index_name, _search_body, 1000).await {
+			Ok(values) => {
+				log::debug!("find_all_from_dsl_body Menu Items found: {:?}", values.len());
+				Ok(values.iter().map(|_value| serde_json::from_value(_value.clone())).collect::<Result<Vec<MenuItem>, serde_json::Error>>()?)
+			},
+			Err(error) => {
+				Err(error)
+			}
+		};
+	}
 	let page_number: i64 = match _page_number {
 		Some(value) => value,
 		None => 0
@@ -382,13 +397,13 @@ pub async fn menu_items_from_role(
 		None => 10000
 	};
 
-	match find_from_dsl_body(_index_name, _search_body, page_number, page_size).await {
+	match _store.find_from_dsl_body(_index_name, _search_body, page_number, page_size).await {
 		Ok(values) => {
 			log::debug!("find_from_dsl_body Menu Items found: {:?}", values.len());
-			Ok(values.iter().map(|_value| serde_json::from_value(_value.clone()).unwrap()).collect::<Vec<MenuItem>>())
+			Ok(values.iter().map(|_value| serde_json::from_value(_value.clone())).collect::<Result<Vec<MenuItem>, serde_json::Error>>()?)
 		},
 		Err(error) => {
-			Err(Error::new(ErrorKind::InvalidData.into(), error))
+			Err(error)
 		}
 	}
 }
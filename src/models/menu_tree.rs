@@ -1,9 +1,10 @@
 use salvo::macros::Extractible;
 use serde::{Deserialize, Serialize};
 use serde_json::json;
-use std::{io::ErrorKind, io::Error};
 
-use crate::controller::opensearch::{IndexDocument, get_by_id};
+use crate::controller::{opensearch::IndexDocument, store::DictionaryStore};
+use crate::controller::search::search_body;
+use crate::error::DictionaryError;
 
 #[derive(Deserialize, Extractible, Debug, Clone)]
 #[salvo(extract(default_source(from = "body")))]
@@ -44,6 +45,7 @@ pub struct MenuTree {
     pub client_id: Option<String>,
     pub role_id: Option<String>,
     pub user_id: Option<String>,
+    pub version: Option<i64>,
     // Tree menu childs
     pub children: Option<Vec<MenuTree>>
 }
@@ -64,6 +66,7 @@ impl Default for MenuTree {
 			client_id: None,
 			role_id: None,
 			user_id: None,
+			version: None,
 			// Tree menu childs
 			children: None
         }
@@ -108,6 +111,10 @@ impl IndexDocument for MenuTree {
 		})
 	}
 
+	fn version(self: &Self) -> Option<i64> {
+		self.version
+	}
+
 	fn index_name(self: &Self) -> String {
 		match &self.index_value {
 			Some(value) => value.to_string(),
@@ -116,28 +123,19 @@ impl IndexDocument for MenuTree {
 	}
 
 	fn find(self: &Self, _search_value: String) -> serde_json::Value {
-		let mut query: String = "*".to_owned();
-		query.push_str(&_search_value.to_owned());
-		query.push_str(&"*".to_owned());
-
-		json!({
-			"query": {
-				"query_string": {
-					"query": query
-				}
-			}
-		})
+		search_body(&_search_value, &["name^3"])
 	}
 }
 
 
 pub async fn menu_tree_from_id(
+	_store: &dyn DictionaryStore,
 	_id: Option<String>,
 	_dictionary_code: Option<&String>
-) -> Result<MenuTree, std::io::Error> {
+) -> Result<MenuTree, DictionaryError> {
 	if _id.is_none() || _id.as_deref().map_or(false, |s| s.trim().is_empty()) {
 		return Err(
-			Error::new(ErrorKind::InvalidData.into(), "Menu Tree Identifier is Mandatory")
+			DictionaryError::BadRequest("Menu Tree Identifier is Mandatory".to_owned())
 		);
 	}
 
@@ -153,9 +151,9 @@ pub async fn menu_tree_from_id(
 	let mut _document: MenuTree = MenuTree::from_id(_id);
 	_document.index_value = Some(_index_name);
     let _menu_document: &dyn IndexDocument = &_document;
-    match get_by_id(_menu_document).await {
+    match _store.get_by_id(_menu_document).await {
         Ok(value) => {
-			let mut menu: MenuTree = serde_json::from_value(value).unwrap();
+			let mut menu: MenuTree = serde_json::from_value(value)?;
 			log::debug!("Finded Menu `{:?}` Tree Value: {:?}", menu.name, menu.id);
 			// sort menu children nodes by sequence
 			if let Some(ref mut children) = menu.children {
@@ -165,7 +163,7 @@ pub async fn menu_tree_from_id(
         },
         Err(error) => {
 			log::error!("{}", error);
-            Err(Error::new(ErrorKind::InvalidData.into(), error))
+            Err(error)
         },
     }
 }
@@ -10,8 +10,12 @@ pub mod role;
 
 use serde::{Deserialize, Serialize};
 use salvo::prelude::*;
-use std::{io::ErrorKind, io::Error};
-use crate::controller::opensearch::exists_index;
+use serde_json::Value;
+use crate::controller::{opensearch::IndexDocument, store::DictionaryStore, topic::TopicRegistry};
+use crate::error::DictionaryError;
+
+//	Base name of dictionary indices, the index name adds language, client and dictionary code
+pub const DICTIONARY_INDICES: [&str; 7] = ["browser", "form", "menu_item", "menu_tree", "process", "role", "window"];
 
 #[derive(Deserialize, Serialize, Extractible, Debug, Clone)]
 pub struct Metadata {
@@ -60,14 +64,15 @@ fn client_index_only(
 
 
 async fn get_index_name(
+	_store: &dyn DictionaryStore,
 	_index_name: String,
 	_language: Option<&String>,
 	_dictionary_code: Option<&String>
-) -> Result<String, std::io::Error> {
+) -> Result<String, DictionaryError> {
 	//  Validate
 	if _language.is_none() || _language.as_deref().map_or(false, |s| s.trim().is_empty()) {
 		return Err(
-			Error::new(ErrorKind::InvalidData.into(), "Language is Mandatory")
+			DictionaryError::BadRequest("Language is Mandatory".to_owned())
 		);
 	}
 
@@ -79,18 +84,145 @@ async fn get_index_name(
 		}
 	}
 
-	//  Find index
-	match exists_index(_language_index.to_owned()).await {
-		Ok(_) => {
-			log::debug!("Find with language index `{:}`", _language_index);
+	//  Find index, the alias is used to search because it always points to a complete index
+	match _store.get_alias_index(_language_index.to_owned()).await {
+		Ok(Some(physical_index)) => {
+			log::debug!("Find with language index `{:}` ({:})", _language_index, physical_index);
 			Ok(_language_index)
 		},
-		Err(error) => {
-			log::warn!("No menu item index `{:}`", _language_index);
-			log::error!("No role index `{:}`", _language_index);
+		Ok(None) => {
+			log::error!("No index found `{:}`", _language_index);
 			return Err(
-				Error::new(ErrorKind::InvalidData.into(), error)
+				DictionaryError::IndexMissing(format!("No Index Found `{:}`", _language_index))
 			)
+		},
+		Err(error) => {
+			log::error!("No index `{:}`", _language_index);
+			return Err(error)
+		}
+	}
+}
+
+
+//	Limit of ids by batch get request
+pub const MAX_BATCH_IDS: usize = 100;
+
+//	Body of batch get routes, as `{ "ids": ["<uuid>", ...] }`
+#[derive(Deserialize, Extractible, Debug, Clone)]
+#[salvo(extract(default_source(from = "body")))]
+pub struct BatchGetRequest {
+	pub ids: Option<Vec<String>>
+}
+
+//	Documents found by a batch get and the requested ids without document
+pub struct BatchValues {
+	pub values: Vec<Value>,
+	pub missing_ids: Vec<String>,
+}
+
+
+//	Get many documents of an index in one call, the repeated ids are
+//	requested once
+async fn get_by_ids(
+	_store: &dyn DictionaryStore,
+	_index_name: String,
+	_ids: Option<Vec<String>>,
+	_language: Option<&String>,
+	_dictionary_code: Option<&String>
+) -> Result<BatchValues, DictionaryError> {
+	let mut ids: Vec<String> = Vec::new();
+	for id in _ids.unwrap_or_default() {
+		let id: String = id.trim().to_owned();
+		if !id.is_empty() && !ids.contains(&id) {
+			ids.push(id);
 		}
 	}
+	if ids.is_empty() {
+		return Err(
+			DictionaryError::BadRequest("IDs are Mandatory".to_owned())
+		);
+	}
+	if ids.len() > MAX_BATCH_IDS {
+		return Err(
+			DictionaryError::BadRequest(format!("Too many IDs ({}), the limit is {}", ids.len(), MAX_BATCH_IDS))
+		);
+	}
+
+	let _index_name: String = get_index_name(_store, _index_name, _language, _dictionary_code).await?;
+	let documents: Vec<Option<Value>> = match _store.get_by_ids(_index_name.to_owned(), &ids).await {
+		Ok(value) => value,
+		Err(error) => {
+			log::error!("{}", error);
+			return Err(error)
+		}
+	};
+	let mut batch: BatchValues = BatchValues {
+		values: Vec::with_capacity(documents.len()),
+		missing_ids: Vec::new(),
+	};
+	for (id, document) in ids.into_iter().zip(documents) {
+		match document {
+			Some(value) => batch.values.push(value),
+			None => batch.missing_ids.push(id)
+		}
+	}
+	log::debug!("Batch get {:?}: {} found, {} missing", _index_name, batch.values.len(), batch.missing_ids.len());
+	Ok(batch)
+}
+
+//	Handlers of Kafka topics with the dictionary documents
+pub fn dictionary_topic_registry() -> TopicRegistry {
+	TopicRegistry::new()
+		.register_document::<browser::Browser>("browser")
+		.register_document::<form::Form>("form")
+		.register_document::<menu_item::MenuItem>("menu_item")
+		.register_document::<menu_tree::MenuTree>("menu_tree")
+		.register_document::<process::Process>("process")
+		.register_document::<role::Role>("role")
+		.register_document::<window::Window>("window")
+}
+
+//	Get a document definition from index (or alias) name, used for admin
+//	operations over indices
+pub fn index_document_from_name(
+	_index_name: &str
+) -> Option<Box<dyn IndexDocument>> {
+	let index_value: Option<String> = Some(_index_name.to_lowercase());
+	if _index_name.starts_with("menu_item") {
+		return Some(Box::new(menu_item::MenuItem {
+			index_value,
+			..menu_item::MenuItem::default()
+		}));
+	} else if _index_name.starts_with("menu_tree") {
+		return Some(Box::new(menu_tree::MenuTree {
+			index_value,
+			..menu_tree::MenuTree::default()
+		}));
+	} else if _index_name.starts_with("role") {
+		return Some(Box::new(role::Role {
+			index_value,
+			..role::Role::default()
+		}));
+	} else if _index_name.starts_with("process") {
+		return Some(Box::new(process::Process {
+			index_value,
+			..process::Process::default()
+		}));
+	} else if _index_name.starts_with("browser") {
+		return Some(Box::new(browser::Browser {
+			index_value,
+			..browser::Browser::default()
+		}));
+	} else if _index_name.starts_with("window") {
+		return Some(Box::new(window::Window {
+			index_value,
+			..window::Window::default()
+		}));
+	} else if _index_name.starts_with("form") {
+		return Some(Box::new(form::Form {
+			index_value,
+			..form::Form::default()
+		}));
+	}
+	None
 }
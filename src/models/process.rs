@@ -1,9 +1,11 @@
 use serde::{Deserialize, Serialize};
 use salvo::prelude::*;
 use serde_json::{json, Value};
-use std::{io::ErrorKind, io::Error};
 
-use crate::{controller::opensearch::{IndexDocument, find, get_by_id}, models::{generic::DependendField, get_index_name}};
+use crate::{controller::{opensearch::IndexDocument, store::DictionaryStore}, models::{generic::DependendField, get_by_ids, get_index_name, BatchValues}};
+use crate::controller::analysis::{analysis_settings, index_analyzer};
+use crate::controller::search::{is_include_matches, search_body, search_matches, search_mode_body, with_highlight, PageRequest, SearchHits, SearchMatch, SearchMode};
+use crate::error::DictionaryError;
 
 #[derive(Deserialize, Extractible, Debug, Clone)]
 #[salvo(extract(default_source(from = "body")))]
@@ -18,7 +20,22 @@ pub struct ProcessResponse {
 
 #[derive(Serialize, Debug, Clone)]
 pub struct ProcessListResponse {
-    pub processes: Option<Vec<Process>>
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub processes: Option<Vec<Process>>,
+    //	Entities with score and highlight, instead of `processes` when `include_matches` is requested
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub matches: Option<Vec<SearchMatch<Process>>>,
+    //	Count of all matches of search
+    pub total: i64,
+    //	Token of `page_token` to get the next page, none on the last page
+    pub next_page_token: Option<String>
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct ProcessBatchResponse {
+	pub processes: Vec<Process>,
+	//	Requested ids without process
+	pub missing_ids: Vec<String>
 }
 
 impl Default for ProcessResponse {
@@ -78,6 +95,7 @@ pub struct Process {
     pub client_id: Option<String>,
     pub role_id: Option<String>,
     pub user_id: Option<String>,
+    pub version: Option<i64>,
 	//	Parameters
     pub has_parameters: Option<bool>,
     pub parameters: Option<Vec<ProcessParameters>>
@@ -159,6 +177,7 @@ impl Default for Process {
 			client_id: None,
             role_id: None,
             user_id: None,
+            version: None,
 			// Parameters
             parameters: None,
             has_parameters: None
@@ -180,7 +199,9 @@ impl Process {
 
 impl IndexDocument for Process {
 	fn mapping(self: &Self) -> serde_json::Value {
+		let analyzer: String = index_analyzer(&self.index_name());
 		json!({
+			"settings": analysis_settings(),
 			"mappings" : {
 				"properties" : {
 					"uuid" : { "type" : "keyword" },
@@ -189,12 +210,14 @@ impl IndexDocument for Process {
 					"code" : { "type" : "keyword" },
 					"name": {
 						"type": "text",
+						"analyzer": analyzer,
 						"fields": {
-							"keyword": { "type": "keyword" }
+							"keyword": { "type": "keyword" },
+							"as_you_type": { "type": "search_as_you_type", "analyzer": analyzer }
 						}
 					},
-					"description" : { "type" : "text" },
-					"help" : { "type" : "text" },
+					"description" : { "type" : "text", "analyzer" : analyzer },
+					"help" : { "type" : "text", "analyzer" : analyzer },
 					"is_report" : { "type" : "boolean" }
 				}
 			}
@@ -212,6 +235,10 @@ impl IndexDocument for Process {
 		})
 	}
 
+	fn version(self: &Self) -> Option<i64> {
+		self.version
+	}
+
 	fn index_name(self: &Self) -> String {
 		match &self.index_value {
 			Some(value) => value.to_string(),
@@ -220,17 +247,7 @@ impl IndexDocument for Process {
 	}
 
 	fn find(self: &Self, _search_value: String) -> serde_json::Value {
-		let mut query: String = "*".to_owned();
-		query.push_str(&_search_value.to_owned());
-		query.push_str(&"*".to_owned());
-
-		json!({
-			"query": {
-				"query_string": {
-					"query": query
-				}
-			}
-		})
+		search_body(&_search_value, &["name^3", "code^2", "description", "help"])
 	}
 }
 
@@ -265,33 +282,34 @@ pub struct Workflow {
 }
 
 
-pub fn parse_process(value: Value) -> Process {
-	let mut process: Process = serde_json::from_value(value).unwrap();
+pub fn parse_process(value: Value) -> Result<Process, DictionaryError> {
+	let mut process: Process = serde_json::from_value(value)?;
 
 	// sort process parameter list by sequence
 	if let Some(ref mut parameters) = process.parameters {
 		parameters.sort_by_key(|parameter: &ProcessParameters| parameter.sequence.clone().unwrap_or(0));
 	}
-	process.to_owned()
+	Ok(process.to_owned())
 }
 
 
 pub async fn process_from_id(
+	_store: &dyn DictionaryStore,
 	_id: Option<String>,
 	_language: Option<&String>,
 	_dictionary_code: Option<&String>
-) -> Result<Process, String> {
+) -> Result<Process, DictionaryError> {
 	if _id.is_none() || _id.as_deref().map_or(false, |s| s.trim().is_empty()) {
 		return Err(
-			Error::new(ErrorKind::InvalidData.into(), "Process/Report Identifier is Mandatory").to_string()
+			DictionaryError::BadRequest("Process/Report Identifier is Mandatory".to_owned())
 		);
 	}
 
-	let _index_name: String = match get_index_name("process".to_string(), _language, _dictionary_code).await {
+	let _index_name: String = match get_index_name(_store, "process".to_string(), _language, _dictionary_code).await {
 		Ok(index_name) => index_name,
 		Err(error) => {
 			log::error!("Process/Report index name error: {:?}", error.to_string());
-			return Err(error.to_string())
+			return Err(error)
 		}
 	};
 	log::debug!("Process/Report index to search {:}", _index_name);
@@ -299,9 +317,9 @@ pub async fn process_from_id(
 	let mut _document: Process = Process::from_id(_id);
     _document.index_value = Some(_index_name);
     let _process_document: &dyn IndexDocument = &_document;
-    match get_by_id(_process_document).await {
+    match _store.get_by_id(_process_document).await {
         Ok(value) => {
-			let process: Process = parse_process(value);
+			let process: Process = parse_process(value)?;
 			log::debug!("Finded Process/Report {:?} Value: {:?}", process.name, process.id);
 			Ok(process)
         },
@@ -312,22 +330,49 @@ pub async fn process_from_id(
     }
 }
 
+pub async fn processes_from_ids(
+	_store: &dyn DictionaryStore,
+	_ids: Option<Vec<String>>,
+	_language: Option<&String>,
+	_dictionary_code: Option<&String>
+) -> Result<ProcessBatchResponse, DictionaryError> {
+	let batch: BatchValues = get_by_ids(_store, "process".to_string(), _ids, _language, _dictionary_code).await?;
+	let mut processes_list: Vec<Process> = Vec::with_capacity(batch.values.len());
+	for value in batch.values {
+		processes_list.push(parse_process(value)?);
+	}
+	Ok(ProcessBatchResponse {
+		processes: processes_list,
+		missing_ids: batch.missing_ids
+	})
+}
+
+#[allow(clippy::too_many_arguments)]
 pub async fn processes(
+	_store: &dyn DictionaryStore,
 	_language: Option<&String>,
 	_search_value: Option<&String>,
+	_search_mode: Option<&String>,
+	_include_matches: Option<&String>,
+	_page_size: Option<&String>,
+	_page_token: Option<&String>,
 	_dictionary_code: Option<&String>
-) -> Result<ProcessListResponse, std::io::Error> {
+) -> Result<ProcessListResponse, DictionaryError> {
 	let _search_value: String = match _search_value {
         Some(value) => value.clone(),
         None => "".to_owned()
     };
 
+	let _search_mode: SearchMode = SearchMode::from_value(_search_mode)?;
+	let _include_matches: bool = is_include_matches(_include_matches)?;
+	let _page: PageRequest = PageRequest::from_values(_page_size, _page_token)?;
+
 	//  Find index
-	let _index_name: String = match get_index_name("process".to_string(), _language, _dictionary_code).await {
+	let _index_name: String = match get_index_name(_store, "process".to_string(), _language, _dictionary_code).await {
 		Ok(index_name) => index_name,
 		Err(error) => {
 			log::error!("Process/Report index name error: {:?}", error.to_string());
-			return Err(Error::new(ErrorKind::InvalidData.into(), error))
+			return Err(error)
 		}
 	};
 	log::debug!("Process/Report index to search {:}", _index_name);
@@ -335,19 +380,36 @@ pub async fn processes(
 	let mut _document: Process = Process::default();
     _document.index_value = Some(_index_name);
     let _process_document: &dyn IndexDocument = &_document;
-    match find(_process_document, _search_value, 0, 10).await {
-        Ok(values) => {
-            let mut processes_list: Vec<Process> = vec![];
-            for value in values {
-				let process: Process = parse_process(value);
-
-                processes_list.push(process.to_owned());
-            }
-
-            Ok(ProcessListResponse {
-                processes: Some(processes_list)
-            })
-        },
-        Err(error) => Err(Error::new(ErrorKind::InvalidData.into(), error))
+    let mut _body: Value = search_mode_body(_process_document, _search_value, &_search_mode);
+    if _include_matches {
+        _body = with_highlight(_body);
+    }
+    let result: SearchHits = match _store.find_hits_from_dsl_body(_process_document.index_name(), _body, _page.from, _page.size).await {
+        Ok(value) => value,
+        Err(error) => {
+            log::error!("{}", error);
+            return Err(error)
+        }
+    };
+    let next_page_token: Option<String> = _page.next_page_token(result.total);
+    if _include_matches {
+        return Ok(ProcessListResponse {
+            processes: None,
+            matches: Some(search_matches(result.hits, parse_process)?),
+            total: result.total,
+            next_page_token
+        });
+    }
+
+    let mut processes_list: Vec<Process> = vec![];
+    for hit in result.hits {
+        let process: Process = parse_process(hit.source)?;
+        processes_list.push(process);
     }
+    Ok(ProcessListResponse {
+        processes: Some(processes_list),
+        matches: None,
+        total: result.total,
+        next_page_token
+    })
 }
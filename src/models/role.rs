@@ -1,11 +1,13 @@
 use serde::{Deserialize, Serialize};
 use salvo::prelude::*;
 use serde_json::json;
-use std::{io::ErrorKind, io::Error};
 
-use crate::controller::opensearch::{IndexDocument, get_by_id, exists_index};
+use crate::controller::{opensearch::IndexDocument, store::DictionaryStore};
 
 use super::client_index_only;
+use crate::controller::analysis::{analysis_settings, index_analyzer};
+use crate::controller::search::search_body;
+use crate::error::DictionaryError;
 
 #[derive(Deserialize, Extractible, Debug, Clone)]
 #[salvo(extract(default_source(from = "body")))]
@@ -46,6 +48,7 @@ pub struct Role {
     pub client_id: Option<String>,
     pub role_id: Option<String>,
     pub user_id: Option<String>,
+    pub version: Option<i64>,
 	// Access
 	pub window_access: Option<Vec<Option<String>>>,
 	pub process_access: Option<Vec<Option<String>>>,
@@ -71,6 +74,7 @@ impl Default for Role {
 			client_id: None,
 			role_id: None,
 			user_id: None,
+			version: None,
 			// Access
 			window_access: None,
 			process_access: None,
@@ -96,7 +100,9 @@ impl Role {
 
 impl IndexDocument for Role {
 	fn mapping(self: &Self) -> serde_json::Value {
+		let analyzer: String = index_analyzer(&self.index_name());
 		json!({
+			"settings": analysis_settings(),
 			"mappings": {
 				"properties": {
 					"uuid": { "type": "keyword" },
@@ -105,11 +111,12 @@ impl IndexDocument for Role {
 					"tree_id": { "type": "integer" },
 					"name": {
 						"type": "text",
+						"analyzer": analyzer,
 						"fields": {
 							"keyword": { "type": "keyword" }
 						}
 					},
-					"description": { "type": "text" }
+					"description": { "type": "text", "analyzer": analyzer }
 				}
 			}
 		})
@@ -126,6 +133,10 @@ impl IndexDocument for Role {
 		})
 	}
 
+	fn version(self: &Self) -> Option<i64> {
+		self.version
+	}
+
 	fn index_name(self: &Self) -> String {
 		match &self.index_value {
 			Some(value) => value.to_string(),
@@ -134,36 +145,27 @@ impl IndexDocument for Role {
 	}
 
 	fn find(self: &Self, _search_value: String) -> serde_json::Value {
-		let mut query: String = "*".to_owned();
-		query.push_str(&_search_value.to_owned());
-		query.push_str(&"*".to_owned());
-
-		json!({
-			"query": {
-				"query_string": {
-					"query": query
-				}
-			}
-		})
+		search_body(&_search_value, &["name^3", "description"])
 	}
 }
 
 pub async fn role_from_id(
+	_store: &dyn DictionaryStore,
 	_uuid: Option<&String>,
 	_client_uuid: Option<&String>,
 	_dictionary_code: Option<&String>
-) -> Result<Role, String> {
+) -> Result<Role, DictionaryError> {
 	if _uuid.is_none() || _uuid.as_deref().map_or(false, |s| s.trim().is_empty()) {
 		return Err(
-			Error::new(ErrorKind::InvalidData.into(), "Role Identifier is Mandatory").to_string()
+			DictionaryError::BadRequest("Role Identifier is Mandatory".to_owned())
 		);
 	}
 
-	let _index_name: String = match get_index_name(_client_uuid).await {
+	let _index_name: String = match get_index_name(_store, _client_uuid).await {
 		Ok(index_name) => index_name,
 		Err(error) => {
 			log::error!("Role index name error: {:?}", error.to_string());
-			return Err(error.to_string())
+			return Err(error)
 		}
 	};
 	log::debug!("Role index to search {:}", _index_name);
@@ -171,7 +173,7 @@ pub async fn role_from_id(
 	let mut _document: Role = Role::from_id(_uuid);
 	_document.index_value = Some(_index_name);
     let _role_document: &dyn IndexDocument = &_document;
-    match get_by_id(_role_document).await {
+    match _store.get_by_id(_role_document).await {
         Ok(value) => {
 			match serde_json::from_value::<Role>(value) {
 				Ok(role) => {
@@ -180,7 +182,7 @@ pub async fn role_from_id(
 				},
 				Err(error) => {
 					log::error!("{}", error);
-					Err(error.to_string())
+					Err(error.into())
 				},
 			}
 		},
@@ -192,11 +194,12 @@ pub async fn role_from_id(
 }
 
 async fn get_index_name(
+	_store: &dyn DictionaryStore,
 	_client_uuid: Option<&String>
-) -> Result<String, std::io::Error> {
+) -> Result<String, DictionaryError> {
 	if _client_uuid.is_none() || _client_uuid.as_deref().map_or(false, |s| s.trim().is_empty()) {
 		return Err(
-			Error::new(ErrorKind::InvalidData.into(), "Client is Mandatory")
+			DictionaryError::BadRequest("Client is Mandatory".to_owned())
 		);
 	}
 
@@ -204,14 +207,18 @@ async fn get_index_name(
 	let _index: String = client_index_only(_base_index.to_owned(), _client_uuid);
 
 	//  Find index
-	match exists_index(_index.to_owned()).await {
-		Ok(_) => {
-			log::debug!("Find with role index index `{:}`", _index);
+	match _store.get_alias_index(_index.to_owned()).await {
+		Ok(Some(physical_index)) => {
+			log::debug!("Find with role index index `{:}` ({:})", _index, physical_index);
 			Ok(_index)
 		},
-		Err(_) => {
+		Ok(None) => {
 			log::error!("No role index found `{:}`", _index);
-            return Err(Error::new(ErrorKind::InvalidData.into(), "No Index Found"))
+			Err(DictionaryError::IndexMissing(format!("No Index Found `{:}`", _index)))
+		},
+		Err(error) => {
+			log::error!("No role index `{:}`: {}", _index, error);
+			Err(error)
 		}
 	}
 }
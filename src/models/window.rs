@@ -1,9 +1,11 @@
 use serde::{Deserialize, Serialize};
 use salvo::prelude::*;
 use serde_json::{json, Value};
-use std::{io::ErrorKind, io::Error};
 
-use crate::{controller::opensearch::{IndexDocument, find, get_by_id}, models::{generic::DependendField, get_index_name}};
+use crate::{controller::{opensearch::IndexDocument, store::DictionaryStore}, models::{generic::DependendField, get_by_ids, get_index_name, BatchValues}};
+use crate::controller::analysis::{analysis_settings, index_analyzer};
+use crate::controller::search::{is_include_matches, search_body, search_matches, search_mode_body, with_highlight, PageRequest, SearchHits, SearchMatch, SearchMode};
+use crate::error::DictionaryError;
 
 #[derive(Deserialize, Extractible, Debug, Clone)]
 #[salvo(extract(default_source(from = "body")))]
@@ -18,7 +20,22 @@ pub struct WindowResponse {
 
 #[derive(Serialize, Debug, Clone)]
 pub struct WindowListResponse {
-    pub windows: Option<Vec<Window>>
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub windows: Option<Vec<Window>>,
+    //	Entities with score and highlight, instead of `windows` when `include_matches` is requested
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub matches: Option<Vec<SearchMatch<Window>>>,
+    //	Count of all matches of search
+    pub total: i64,
+    //	Token of `page_token` to get the next page, none on the last page
+    pub next_page_token: Option<String>
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct WindowBatchResponse {
+	pub windows: Vec<Window>,
+	//	Requested ids without window
+	pub missing_ids: Vec<String>
 }
 
 impl Default for WindowResponse {
@@ -47,6 +64,7 @@ pub struct Window {
     pub client_id: Option<String>,
     pub role_id: Option<String>,
     pub user_id: Option<String>,
+    pub version: Option<i64>,
 	//	Tabs
     pub tabs: Option<Vec<WindowTab>>,
 }
@@ -180,6 +198,7 @@ impl Default for Window {
             language: None,
             role_id: None,
             user_id: None,
+            version: None,
             is_sales_transaction: None,
             tabs: None,
             window_type: None
@@ -201,7 +220,9 @@ impl Window {
 
 impl IndexDocument for Window {
 	fn mapping(self: &Self) -> serde_json::Value {
+		let analyzer: String = index_analyzer(&self.index_name());
 		json!({
+			"settings": analysis_settings(),
 			"mappings" : {
 				"properties" : {
 					"uuid" : { "type" : "keyword" },
@@ -209,12 +230,14 @@ impl IndexDocument for Window {
 					"internal_id" : { "type" : "integer" },
 					"name": {
 						"type": "text",
+						"analyzer": analyzer,
 						"fields": {
-							"keyword": { "type": "keyword" }
+							"keyword": { "type": "keyword" },
+							"as_you_type": { "type": "search_as_you_type", "analyzer": analyzer }
 						}
 					},
-					"description" : { "type" : "text" },
-					"help" : { "type" : "text" }
+					"description" : { "type" : "text", "analyzer" : analyzer },
+					"help" : { "type" : "text", "analyzer" : analyzer }
 				}
 			}
 		})
@@ -231,6 +254,10 @@ impl IndexDocument for Window {
 		})
 	}
 
+	fn version(self: &Self) -> Option<i64> {
+		self.version
+	}
+
 	fn index_name(self: &Self) -> String {
 		match &self.index_value {
 			Some(value) => value.to_string(),
@@ -239,17 +266,7 @@ impl IndexDocument for Window {
 	}
 
 	fn find(self: &Self, _search_value: String) -> serde_json::Value {
-		let mut query: String = "*".to_owned();
-		query.push_str(&_search_value.to_owned());
-		query.push_str(&"*".to_owned());
-
-		json!({
-			"query": {
-				"query_string": {
-					"query": query
-				}
-			}
-		})
+		search_body(&_search_value, &["name^3", "description", "help"])
 	}
 }
 
@@ -299,8 +316,8 @@ pub struct Table {
 }
 
 
-pub fn parse_window(value: Value) -> Window {
-	let mut window: Window = serde_json::from_value(value).unwrap();
+pub fn parse_window(value: Value) -> Result<Window, DictionaryError> {
+	let mut window: Window = serde_json::from_value(value)?;
 
 	// sort tabs list by sequence
 	if let Some(ref mut tabs) = window.tabs {
@@ -317,26 +334,27 @@ pub fn parse_window(value: Value) -> Window {
 			}
 		}
 	}
-	window.to_owned()
+	Ok(window.to_owned())
 }
 
 
 pub async fn window_from_id(
+	_store: &dyn DictionaryStore,
 	_id: Option<String>,
 	_language: Option<&String>,
 	_dictionary_code: Option<&String>
-) -> Result<Window, String> {
+) -> Result<Window, DictionaryError> {
 	if _id.is_none() || _id.as_deref().map_or(false, |s| s.trim().is_empty()) {
 		return Err(
-			Error::new(ErrorKind::InvalidData.into(), "Window Identifier is Mandatory").to_string()
+			DictionaryError::BadRequest("Window Identifier is Mandatory".to_owned())
 		);
 	}
 
-	let _index_name: String = match get_index_name("window".to_string(), _language,_dictionary_code).await {
+	let _index_name: String = match get_index_name(_store, "window".to_string(), _language,_dictionary_code).await {
 		Ok(index_name) => index_name,
 		Err(error) => {
 			log::error!("Window index name error to {:?}: {:?}", _id.to_owned(), error.to_string());
-			return Err(error.to_string())
+			return Err(error)
 		}
 	};
 	log::debug!("Window index to search {:}", _index_name);
@@ -344,9 +362,9 @@ pub async fn window_from_id(
 	let mut _document: Window = Window::from_id(_id.to_owned());
     _document.index_value = Some(_index_name);
     let _window_document: &dyn IndexDocument = &_document;
-    match get_by_id(_window_document).await {
+    match _store.get_by_id(_window_document).await {
         Ok(value) => {
-			let window: Window = parse_window(value);
+			let window: Window = parse_window(value)?;
 			log::debug!("Finded Window {:?} Value: {:?}", window.name, window.id);
             Ok(window)
         },
@@ -357,22 +375,49 @@ pub async fn window_from_id(
     }
 }
 
+pub async fn windows_from_ids(
+	_store: &dyn DictionaryStore,
+	_ids: Option<Vec<String>>,
+	_language: Option<&String>,
+	_dictionary_code: Option<&String>
+) -> Result<WindowBatchResponse, DictionaryError> {
+	let batch: BatchValues = get_by_ids(_store, "window".to_string(), _ids, _language, _dictionary_code).await?;
+	let mut windows_list: Vec<Window> = Vec::with_capacity(batch.values.len());
+	for value in batch.values {
+		windows_list.push(parse_window(value)?);
+	}
+	Ok(WindowBatchResponse {
+		windows: windows_list,
+		missing_ids: batch.missing_ids
+	})
+}
+
+#[allow(clippy::too_many_arguments)]
 pub async fn windows(
+	_store: &dyn DictionaryStore,
 	_language: Option<&String>,
 	_search_value: Option<&String>,
+	_search_mode: Option<&String>,
+	_include_matches: Option<&String>,
+	_page_size: Option<&String>,
+	_page_token: Option<&String>,
 	_dictionary_code: Option<&String>
-) -> Result<WindowListResponse, std::io::Error> {
+) -> Result<WindowListResponse, DictionaryError> {
 	let _search_value: String = match _search_value {
         Some(value) => value.clone(),
         None => "".to_owned()
     };
 
+	let _search_mode: SearchMode = SearchMode::from_value(_search_mode)?;
+	let _include_matches: bool = is_include_matches(_include_matches)?;
+	let _page: PageRequest = PageRequest::from_values(_page_size, _page_token)?;
+
 	//  Find index
-	let _index_name: String = match get_index_name("window".to_string(), _language, _dictionary_code).await {
+	let _index_name: String = match get_index_name(_store, "window".to_string(), _language, _dictionary_code).await {
 		Ok(index_name) => index_name,
 		Err(error) => {
 			log::error!("Window index name error: {:?}", error.to_string());
-			return Err(Error::new(ErrorKind::InvalidData.into(), error))
+			return Err(error)
 		}
 	};
 	log::debug!("Window index to search {:}", _index_name);
@@ -380,21 +425,36 @@ pub async fn windows(
 	let mut _document: Window = Window::default();
     _document.index_value = Some(_index_name);
     let _window_document: &dyn IndexDocument = &_document;
-    match find(_window_document, _search_value, 0, 10).await {
-        Ok(values) => {
-            let mut windows_list: Vec<Window> = vec![];
-            for value in values {
-				let window: Window = parse_window(value);
-                windows_list.push(window.to_owned());
-            }
-
-            Ok(WindowListResponse {
-                windows: Some(windows_list)
-            })
-        },
-		Err(error) => {
-			log::error!("{}", error);
-			Err(Error::new(ErrorKind::InvalidData.into(), error))
-		}
+    let mut _body: Value = search_mode_body(_window_document, _search_value, &_search_mode);
+    if _include_matches {
+        _body = with_highlight(_body);
+    }
+    let result: SearchHits = match _store.find_hits_from_dsl_body(_window_document.index_name(), _body, _page.from, _page.size).await {
+        Ok(value) => value,
+        Err(error) => {
+            log::error!("{}", error);
+            return Err(error)
+        }
+    };
+    let next_page_token: Option<String> = _page.next_page_token(result.total);
+    if _include_matches {
+        return Ok(WindowListResponse {
+            windows: None,
+            matches: Some(search_matches(result.hits, parse_window)?),
+            total: result.total,
+            next_page_token
+        });
+    }
+
+    let mut windows_list: Vec<Window> = vec![];
+    for hit in result.hits {
+        let window: Window = parse_window(hit.source)?;
+        windows_list.push(window);
     }
+    Ok(WindowListResponse {
+        windows: Some(windows_list),
+        matches: None,
+        total: result.total,
+        next_page_token
+    })
 }